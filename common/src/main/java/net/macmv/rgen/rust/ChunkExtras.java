@@ -0,0 +1,55 @@
+package net.macmv.rgen.rust;
+
+import net.minecraft.block.state.IBlockState;
+import net.minecraft.entity.Entity;
+import net.minecraft.entity.EntityList;
+import net.minecraft.nbt.JsonToNBT;
+import net.minecraft.nbt.NBTException;
+import net.minecraft.nbt.NBTTagCompound;
+import net.minecraft.tileentity.TileEntity;
+import net.minecraft.util.math.BlockPos;
+import net.minecraft.world.World;
+import net.minecraft.world.chunk.Chunk;
+
+// The block entities and entities in a chunk, built by rust. Each one is stored as an NBT string.
+public class ChunkExtras {
+  public String[] blockEntities;
+  public String[] entities;
+
+  public void addTo(World world, Chunk chunk) {
+    for (String s : blockEntities) {
+      NBTTagCompound nbt = parse(s);
+      BlockPos pos = new BlockPos(nbt.getInteger("x"), nbt.getInteger("y"), nbt.getInteger("z"));
+
+      // The block creates its own tile entity, and then we merge our data into it. This runs before the chunk is
+      // added to the world, so the tile entity must be built without going through the world, as that would try to
+      // load this chunk again.
+      IBlockState state = chunk.getBlockState(pos);
+      if (!state.getBlock().hasTileEntity(state)) {
+        continue;
+      }
+      TileEntity te = state.getBlock().createTileEntity(world, state);
+      if (te != null) {
+        NBTTagCompound merged = te.writeToNBT(new NBTTagCompound());
+        merged.merge(nbt);
+        te.readFromNBT(merged);
+        chunk.addTileEntity(pos, te);
+      }
+    }
+
+    for (String s : entities) {
+      Entity entity = EntityList.createEntityFromNBT(parse(s), world);
+      if (entity != null) {
+        chunk.addEntity(entity);
+      }
+    }
+  }
+
+  private static NBTTagCompound parse(String s) {
+    try {
+      return JsonToNBT.getTagFromJson(s);
+    } catch (NBTException e) {
+      throw new RuntimeException("invalid NBT from rust: " + s, e);
+    }
+  }
+}
@@ -28,12 +28,15 @@ public class RustGenerator {
   private static native void init_world(long seed);
   private static native void init();
   private static native int reload_generator();
-  private static native void build_chunk(char[] data, int x, int z);
+  private static native ChunkExtras build_chunk(char[] data, int x, int z);
   private static native void build_biomes(byte[] data, int x, int z);
   private static native void build_biomes_region(byte[] data, int cellX, int cellZ, int width, int height);
   private static native String[] debug_info(int x, int y, int z);
   private static native String get_biome_name_at(int x, int y, int z);
   private static native byte get_biome_at(int x, int z);
+  private static native int[] locate_structure(String name, int x, int z, int maxRadius);
+  private static native int[] find_biome(String name, int x, int z, int radius);
+  private static native int[] find_biome_type(String geographic, String climate, int x, int z, int radius);
   private static native OwnedLog wait_for_log();
 
   // Helpers for the rust code.
@@ -220,8 +223,33 @@ public class RustGenerator {
     return get_biome_name_at(x, y, z);
   }
 
-  public static void make_chunk(char[] data, int x, int z) {
-    build_chunk(data, x, z);
+  // Returns the closest structure of the given type, or null if there isn't one within `maxRadius` blocks.
+  public static BlockPos locateStructure(String name, int x, int z, int maxRadius) {
+    return toBlockPos(locate_structure(name, x, z, maxRadius));
+  }
+
+  // Returns the closest column with the given biome, or null if there isn't one within `radius` blocks.
+  public static BlockPos findBiome(String name, int x, int z, int radius) {
+    return toBlockPos(find_biome(name, x, z, radius));
+  }
+
+  // Returns the closest column with the given geographic and climate type (like `hills` and `warm_temperate`), or null
+  // if there isn't one within `radius` blocks.
+  public static BlockPos findBiomeType(String geographic, String climate, int x, int z, int radius) {
+    return toBlockPos(find_biome_type(geographic, climate, x, z, radius));
+  }
+
+  private static BlockPos toBlockPos(int[] pos) {
+    if (pos == null) {
+      return null;
+    }
+
+    return new BlockPos(pos[0], pos[1], pos[2]);
+  }
+
+  // Returns the block entities and entities in the chunk, or null if there aren't any.
+  public static ChunkExtras make_chunk(char[] data, int x, int z) {
+    return build_chunk(data, x, z);
   }
   public static void make_biomes(byte[] biomes, int x, int z) {
     build_biomes(biomes, x, z);
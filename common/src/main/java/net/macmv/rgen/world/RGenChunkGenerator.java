@@ -1,5 +1,6 @@
 package net.macmv.rgen.world;
 
+import net.macmv.rgen.rust.ChunkExtras;
 import net.macmv.rgen.rust.RustGenerator;
 import net.minecraft.block.BlockFalling;
 import net.minecraft.entity.EnumCreatureType;
@@ -38,19 +39,23 @@ public class RGenChunkGenerator extends ChunkGeneratorOverworld {
   public Chunk generateChunk(int x, int z) {
     ChunkPrimer primer = new ChunkPrimer();
 
-    build_rust_chunk(primer, x, z);
+    ChunkExtras extras = build_rust_chunk(primer, x, z);
 
     this.vanillaDecorator.generate(world, x, z, primer);
 
     Chunk chunk = new Chunk(this.world, primer, x, z);
 
+    if (extras != null) {
+      extras.addTo(this.world, chunk);
+    }
+
     RustGenerator.make_biomes(chunk.getBiomeArray(), x, z);
 
     chunk.generateSkylightMap();
     return chunk;
   }
 
-  private void build_rust_chunk(ChunkPrimer primer, int x, int z) {
+  private ChunkExtras build_rust_chunk(ChunkPrimer primer, int x, int z) {
     try {
       // FIXME: Use an access transformer instead.
       Field dataField;
@@ -62,7 +67,7 @@ public class RGenChunkGenerator extends ChunkGeneratorOverworld {
       dataField.setAccessible(true);
 
       char[] data = (char[]) dataField.get(primer);
-      RustGenerator.make_chunk(data, x, z);
+      return RustGenerator.make_chunk(data, x, z);
     } catch (NoSuchFieldException | IllegalAccessException e) {
       throw new RuntimeException(e);
     }
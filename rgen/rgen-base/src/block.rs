@@ -126,6 +126,15 @@ impl BlockState {
     self.check();
   }
 
+  /// Returns the value of the property `key`, if it is set on this block
+  /// state. Block states with a data value have no properties.
+  pub fn prop(&self, key: &str) -> Option<PropValue<'_>> {
+    match self.state {
+      StateOrProps::Props(ref props) => props.entries().find(|(k, _)| *k == key).map(|(_, v)| v),
+      _ => None,
+    }
+  }
+
   /// Sets the property `key` to `value`, returning the updated block state.
   ///
   /// # Panics
@@ -403,6 +412,7 @@ blocks! {
   Concrete => minecraft:concrete[color: COLOR],
   Cobblestone => minecraft:cobblestone,
   MossyCobblestone => minecraft:mossy_cobblestone,
+  StoneBrick => minecraft:stonebrick[variant: ["stonebrick", "mossy_stonebrick", "cracked_stonebrick", "chiseled_stonebrick"]],
   Ice => minecraft:ice,
   PackedIce => minecraft:packed_ice,
   Tallgrass => minecraft:tallgrass[type: ["dead_bush", "tall_grass", "fern"]],
@@ -446,6 +456,24 @@ blocks! {
   RedstoneOre => minecraft:redstone_ore,
   DiamondOre => minecraft:diamond_ore,
   EmeraldOre => minecraft:emerald_ore,
+  Magma => minecraft:magma,
+  OakStairs => minecraft:oak_stairs[
+    facing: ["north", "south", "west", "east"],
+    half: ["top", "bottom"],
+  ],
+  SpruceStairs => minecraft:spruce_stairs[
+    facing: ["north", "south", "west", "east"],
+    half: ["top", "bottom"],
+  ],
+  StoneStairs => minecraft:stone_stairs[
+    facing: ["north", "south", "west", "east"],
+    half: ["top", "bottom"],
+  ],
+  SandstoneStairs => minecraft:sandstone_stairs[
+    facing: ["north", "south", "west", "east"],
+    half: ["top", "bottom"],
+  ],
+  Chest => minecraft:chest[facing: ["north", "south", "west", "east"]],
 
   RgenLog => rgen:log[axis: ["x", "y", "z", "none"], variant: RGEN_WOOD_4_1],
   RgenLog2 => rgen:log2[axis: ["x", "y", "z", "none"], variant: RGEN_WOOD_4_2_LOG],
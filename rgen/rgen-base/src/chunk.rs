@@ -1,6 +1,10 @@
 use smallvec::SmallVec;
 
-use crate::{ChunkRelPos, StateId};
+use crate::{BlockEntity, ChunkRelPos, Entity, StateId};
+
+/// The height of the world. Blocks can be placed at Y levels from 0 up to (but
+/// not including) this.
+pub const WORLD_HEIGHT: i32 = 256;
 
 // Mirrors a ChunkPrimer in minecraft.
 #[derive(Clone)]
@@ -8,9 +12,12 @@ pub struct Chunk {
   data: Box<[u16]>,
 
   surfaces: Box<[[SmallVec<[u8; 2]>; 16]; 16]>,
+
+  block_entities: Vec<(ChunkRelPos, BlockEntity)>,
+  entities:       Vec<(ChunkRelPos, Entity)>,
 }
 
-fn pos_in_world(pos: ChunkRelPos) -> bool { pos.y() >= 0 && pos.y() < 256 }
+fn pos_in_world(pos: ChunkRelPos) -> bool { pos.y() >= 0 && pos.y() < WORLD_HEIGHT }
 
 fn pos_to_index(pos: ChunkRelPos) -> usize {
   ((pos.x() as usize) << 12) | ((pos.z() as usize) << 8) | (pos.y() as usize)
@@ -35,7 +42,12 @@ impl Chunk {
       Box::from_raw(slice_ptr)
     };
 
-    Chunk { data, surfaces: Box::new([const { [const { SmallVec::new_const() }; 16] }; 16]) }
+    Chunk {
+      data,
+      surfaces: Box::new([const { [const { SmallVec::new_const() }; 16] }; 16]),
+      block_entities: vec![],
+      entities: vec![],
+    }
   }
 
   pub fn set(&mut self, pos: ChunkRelPos, block: StateId) {
@@ -67,4 +79,26 @@ impl Chunk {
   pub fn surfaces(&self, column: ChunkRelPos) -> &[u8] {
     &self.surfaces[column.z() as usize][column.x() as usize]
   }
+
+  /// Sets the block entity at the given position, replacing any existing one.
+  pub fn set_block_entity(&mut self, pos: ChunkRelPos, block_entity: BlockEntity) {
+    if !pos_in_world(pos) {
+      return;
+    }
+
+    match self.block_entities.iter_mut().find(|(p, _)| *p == pos) {
+      Some((_, existing)) => *existing = block_entity,
+      None => self.block_entities.push((pos, block_entity)),
+    }
+  }
+
+  pub fn block_entities(&self) -> &[(ChunkRelPos, BlockEntity)] { &self.block_entities }
+
+  pub fn add_entity(&mut self, pos: ChunkRelPos, entity: Entity) {
+    if pos_in_world(pos) {
+      self.entities.push((pos, entity));
+    }
+  }
+
+  pub fn entities(&self) -> &[(ChunkRelPos, Entity)] { &self.entities }
 }
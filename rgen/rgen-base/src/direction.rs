@@ -1,8 +1,7 @@
-// TODO: Most of this should go into `rgen_base`.
+use crate::Pos;
 
-use rgen_base::Pos;
-
-#[derive(Clone, Copy)]
+/// A horizontal direction. North is towards negative Z.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Direction {
   North,
   East,
@@ -10,17 +9,12 @@ pub enum Direction {
   West,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Axis {
   X,
   Z,
 }
 
-pub struct Rectangle {
-  pub min: Pos,
-  pub max: Pos,
-}
-
 impl Direction {
   pub fn dir(&self) -> Pos {
     match self {
@@ -40,7 +34,6 @@ impl Direction {
     }
   }
 
-  #[allow(unused)]
   pub fn opposite(&self) -> Direction {
     match self {
       Direction::North => Direction::South,
@@ -50,7 +43,6 @@ impl Direction {
     }
   }
 
-  #[allow(unused)]
   pub fn axis(&self) -> Axis {
     match self {
       Direction::North | Direction::South => Axis::Z,
@@ -81,12 +73,3 @@ impl Axis {
     }
   }
 }
-
-impl Rectangle {
-  pub fn intersects(&self, other: &Rectangle) -> bool {
-    self.min.x <= other.max.x
-      && self.max.x >= other.min.x
-      && self.min.z <= other.max.z
-      && self.max.z >= other.min.z
-  }
-}
@@ -0,0 +1,71 @@
+use crate::{Nbt, NbtCompound, Pos};
+
+/// Extra data attached to a single block, like the items in a chest or the text
+/// on a sign.
+///
+/// The block entity itself is created by the block it's placed on, and then
+/// `data` is merged into it. This means the block must be placed as well, or
+/// the block entity will be ignored.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockEntity {
+  pub data:       NbtCompound,
+  /// A loot table to fill this container with when it is first opened, like
+  /// `minecraft:chests/village_blacksmith`.
+  pub loot_table: Option<String>,
+}
+
+/// An entity that is spawned in when a chunk is generated, like a villager.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entity {
+  /// The entity id, like `minecraft:villager`.
+  pub id:   String,
+  pub data: NbtCompound,
+}
+
+impl BlockEntity {
+  pub fn new() -> Self { BlockEntity::default() }
+
+  pub fn with_data(mut self, key: impl Into<String>, value: impl Into<Nbt>) -> Self {
+    self.data.insert(key, value);
+    self
+  }
+
+  pub fn with_loot_table(mut self, loot_table: impl Into<String>) -> Self {
+    self.loot_table = Some(loot_table.into());
+    self
+  }
+
+  /// Returns the NBT to merge into the block entity at `pos`.
+  pub fn to_nbt(&self, pos: Pos) -> NbtCompound {
+    let mut nbt = NbtCompound::new().with("x", pos.x).with("y", pos.y).with("z", pos.z);
+    nbt.merge(&self.data);
+    if let Some(loot_table) = &self.loot_table {
+      nbt.insert("LootTable", loot_table.as_str());
+    }
+    nbt
+  }
+}
+
+impl Entity {
+  pub fn new(id: impl Into<String>) -> Self { Entity { id: id.into(), data: NbtCompound::new() } }
+
+  pub fn with_data(mut self, key: impl Into<String>, value: impl Into<Nbt>) -> Self {
+    self.data.insert(key, value);
+    self
+  }
+
+  /// Returns the NBT for this entity, standing in the middle of the block at
+  /// `pos`.
+  pub fn to_nbt(&self, pos: Pos) -> NbtCompound {
+    let mut nbt = NbtCompound::new().with("id", self.id.as_str()).with(
+      "Pos",
+      vec![
+        Nbt::Double(f64::from(pos.x) + 0.5),
+        Nbt::Double(f64::from(pos.y)),
+        Nbt::Double(f64::from(pos.z) + 0.5),
+      ],
+    );
+    nbt.merge(&self.data);
+    nbt
+  }
+}
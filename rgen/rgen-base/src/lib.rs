@@ -1,16 +1,22 @@
 mod biome;
 mod block;
 mod chunk;
+mod direction;
+mod entity;
 mod filter;
 mod iter;
+mod nbt;
 mod pos;
 mod prop;
 
 pub use biome::{Biome, BiomeId};
 pub use block::{BlockData, BlockId, BlockInfo, BlockKind, BlockState, StateId, StateOrProps};
-pub use chunk::Chunk;
+pub use chunk::{Chunk, WORLD_HEIGHT};
+pub use direction::{Axis, Direction};
+pub use entity::{BlockEntity, Entity};
 pub use filter::BlockFilter;
 pub use iter::{BlocksIterExclusive, BlocksIterInclusive};
+pub use nbt::{Nbt, NbtCompound};
 pub use pos::{ChunkPos, ChunkRelPos, Pos};
 pub use prop::{PropMap, PropMapOwned, PropType, PropValue, PropValueOwned};
 
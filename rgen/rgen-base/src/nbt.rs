@@ -0,0 +1,161 @@
+use std::fmt;
+
+/// An NBT tag, as Minecraft stores block entities and entities.
+///
+/// Tags are passed to Minecraft in their string form (SNBT), which is what the
+/// `Display` impl writes out.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Nbt {
+  Byte(i8),
+  Short(i16),
+  Int(i32),
+  Long(i64),
+  Float(f32),
+  Double(f64),
+  String(String),
+  List(Vec<Nbt>),
+  Compound(NbtCompound),
+}
+
+/// A map of NBT tags. Keys are kept in the order they were inserted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NbtCompound {
+  entries: Vec<(String, Nbt)>,
+}
+
+impl NbtCompound {
+  pub fn new() -> Self { NbtCompound { entries: vec![] } }
+
+  pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+  pub fn get(&self, key: &str) -> Option<&Nbt> {
+    self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+  }
+
+  /// Sets the given key, replacing any existing value.
+  pub fn insert(&mut self, key: impl Into<String>, value: impl Into<Nbt>) {
+    let key = key.into();
+    let value = value.into();
+
+    match self.entries.iter_mut().find(|(k, _)| *k == key) {
+      Some((_, v)) => *v = value,
+      None => self.entries.push((key, value)),
+    }
+  }
+
+  /// Builder-style version of [`insert`](Self::insert).
+  pub fn with(mut self, key: impl Into<String>, value: impl Into<Nbt>) -> Self {
+    self.insert(key, value);
+    self
+  }
+
+  /// Inserts all the entries of `other`, replacing any existing values.
+  pub fn merge(&mut self, other: &NbtCompound) {
+    for (key, value) in &other.entries {
+      self.insert(key.clone(), value.clone());
+    }
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = (&str, &Nbt)> {
+    self.entries.iter().map(|(k, v)| (k.as_str(), v))
+  }
+}
+
+macro_rules! from_impl {
+  ($($ty:ty => $variant:ident),* $(,)?) => {
+    $(
+      impl From<$ty> for Nbt {
+        fn from(value: $ty) -> Self { Nbt::$variant(value.into()) }
+      }
+    )*
+  };
+}
+
+from_impl! {
+  i8 => Byte,
+  i16 => Short,
+  i32 => Int,
+  i64 => Long,
+  f32 => Float,
+  f64 => Double,
+  String => String,
+  &str => String,
+  Vec<Nbt> => List,
+  NbtCompound => Compound,
+}
+
+impl fmt::Display for Nbt {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Nbt::Byte(v) => write!(f, "{v}b"),
+      Nbt::Short(v) => write!(f, "{v}s"),
+      Nbt::Int(v) => write!(f, "{v}"),
+      Nbt::Long(v) => write!(f, "{v}L"),
+      Nbt::Float(v) => write!(f, "{v:?}f"),
+      Nbt::Double(v) => write!(f, "{v:?}d"),
+      Nbt::String(v) => write_quoted(f, v),
+      Nbt::List(values) => {
+        write!(f, "[")?;
+        for (i, value) in values.iter().enumerate() {
+          if i != 0 {
+            write!(f, ",")?;
+          }
+          write!(f, "{value}")?;
+        }
+        write!(f, "]")
+      }
+      Nbt::Compound(compound) => write!(f, "{compound}"),
+    }
+  }
+}
+
+impl fmt::Display for NbtCompound {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{{")?;
+    for (i, (key, value)) in self.entries.iter().enumerate() {
+      if i != 0 {
+        write!(f, ",")?;
+      }
+
+      if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || "_-.+".contains(c)) {
+        write!(f, "{key}")?;
+      } else {
+        write_quoted(f, key)?;
+      }
+      write!(f, ":{value}")?;
+    }
+    write!(f, "}}")
+  }
+}
+
+fn write_quoted(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+  write!(f, "\"")?;
+  for c in s.chars() {
+    if c == '"' || c == '\\' {
+      write!(f, "\\")?;
+    }
+    write!(f, "{c}")?;
+  }
+  write!(f, "\"")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn snbt() {
+    let nbt = NbtCompound::new()
+      .with("id", "minecraft:villager")
+      .with("Pos", vec![Nbt::Double(1.5), Nbt::Double(64.0), Nbt::Double(-3.5)])
+      .with("Profession", 2)
+      .with("Count", 3_i8)
+      .with("CustomName", "Bob \"the\" builder")
+      .with("weird key", NbtCompound::new());
+
+    assert_eq!(
+      nbt.to_string(),
+      r#"{id:"minecraft:villager",Pos:[1.5d,64.0d,-3.5d],Profession:2,Count:3b,CustomName:"Bob \"the\" builder","weird key":{}}"#
+    );
+  }
+}
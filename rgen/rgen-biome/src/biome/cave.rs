@@ -1,21 +1,73 @@
-use rgen_base::biome;
+use rgen_base::{biome, block};
 use rgen_placer::chunk_placer;
 
 use super::BiomeBuilder;
 
-#[allow(dead_code)]
 pub fn cave(g: &mut BiomeBuilder) {
   g.id = biome![plains];
   g.color = "#ffffff";
 
-  g.place_chunk(chunk_placer::GlowVine::new());
+  g.place_chunk("glow vine", chunk_placer::GlowVine::new());
 }
 
-#[allow(dead_code)]
 pub fn lush_cave(g: &mut BiomeBuilder) {
   g.id = biome![plains];
   g.color = "#ffffff";
 
-  g.place_chunk(chunk_placer::LushCaveMoss::new());
-  g.place_chunk(chunk_placer::GlowVine::new());
+  g.place_chunk("lush cave moss", chunk_placer::LushCaveMoss::new());
+  g.place_chunk("glow vine", chunk_placer::GlowVine::new());
+}
+
+pub fn dripstone_cave(g: &mut BiomeBuilder) {
+  g.id = biome![plains];
+  g.color = "#ffffff";
+
+  g.place_chunk(
+    "dripstone",
+    chunk_placer::Stalactites {
+      ground:     block![stone].into(),
+      block:      block![stone[variant = "andesite"]],
+      max_length: 4,
+      chance:     0.08,
+    },
+  );
+}
+
+pub fn frozen_cave(g: &mut BiomeBuilder) {
+  g.id = biome![plains];
+  g.color = "#ffffff";
+
+  g.place_chunk(
+    "frozen walls",
+    chunk_placer::CaveSurface {
+      replace: block![stone].into(),
+      floor:   block![packed_ice],
+      ceiling: block![ice],
+      chance:  0.8,
+    },
+  );
+  g.place_chunk(
+    "icicles",
+    chunk_placer::Stalactites {
+      ground:     [block![ice], block![packed_ice]].into(),
+      block:      block![ice],
+      max_length: 3,
+      chance:     0.05,
+    },
+  );
+}
+
+pub fn magma_cave(g: &mut BiomeBuilder) {
+  g.id = biome![plains];
+  g.color = "#ffffff";
+
+  g.place_chunk(
+    "magma floor",
+    chunk_placer::CaveSurface {
+      replace: block![stone].into(),
+      floor:   block![magma],
+      ceiling: block![rgen:basalt[axis = "y"]],
+      chance:  0.6,
+    },
+  );
 }
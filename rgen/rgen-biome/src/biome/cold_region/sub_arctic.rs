@@ -1,5 +1,5 @@
 use rgen_base::{biome, block};
-use rgen_placer::{chunk_placer, placer};
+use rgen_placer::{Placer, chunk_placer, condition, placer};
 
 use crate::builder::{BiomeBuilder, PlacerStage};
 
@@ -554,11 +554,14 @@ pub fn snowy_peak(g: &mut BiomeBuilder) {
 
   g.set_top_block(block![stone]);
   g.set_underwater_block(block![stone]);
-  g.place_chunk(chunk_placer::CheckerboardSurface {
-    replace: block![stone].into(),
-    a:       block![concrete[color = "magenta"]],
-    b:       block![concrete[color = "black"]],
-  });
+  g.place_chunk(
+    "checkerboard",
+    chunk_placer::CheckerboardSurface {
+      replace: block![stone].into(),
+      a:       block![concrete[color = "magenta"]],
+      b:       block![concrete[color = "black"]],
+    },
+  );
 }
 
 #[allow(dead_code)]
@@ -787,7 +790,13 @@ pub fn mossy_shores(g: &mut BiomeBuilder) {
   g.set_top_block(block![gravel]);
   g.add_layer(block![gravel], 2, 4);
 
-  g.place("Mossy Bolders", PlacerStage::Tree, placer::MossBoulder::default());
+  // Keep boulders on the flat parts of the shore, so they don't hang off of
+  // the cliffs.
+  g.place(
+    "Mossy Bolders",
+    PlacerStage::Tree,
+    placer::MossBoulder::default().when(condition::Slope::default()),
+  );
 
   g.place(
     "loose_moss",
@@ -1,6 +1,6 @@
 use rgen_base::{biome, block};
 use rgen_placer::{
-  chunk_placer,
+  chunk_placer, derive_seed,
   noise::{OpenSimplexNoise, SeededNoise},
   placer,
 };
@@ -15,13 +15,16 @@ pub fn ice_spike_beach(g: &mut BiomeBuilder) {
   g.set_top_block(block![stone]);
 
   g.place("Ice spikes", PlacerStage::Tree, placer::IceSpikes::default());
-  g.place_chunk(chunk_placer::SnowOnStoneSurface {
-    noise:       OpenSimplexNoise::new(0),
-    a:           block![snow_layer],
-    add_snow:    0.75,
-    min_snow:    1,
-    place_above: [block![stone]].into(),
-  });
+  g.place_chunk(
+    "snow on stone",
+    chunk_placer::SnowOnStoneSurface {
+      noise:       OpenSimplexNoise::new(derive_seed(g.seed, "snow on stone")),
+      a:           block![snow_layer],
+      add_snow:    0.75,
+      min_snow:    1,
+      place_above: [block![stone]].into(),
+    },
+  );
 
   g.place(
     "gravel_patches",
@@ -51,7 +54,7 @@ pub fn glacier(g: &mut BiomeBuilder) {
   g.set_top_block(block![packed_ice]);
   g.add_layer(block![packed_ice], 20, 25);
 
-  g.place_chunk(chunk_placer::Crevasse::new());
+  g.place_chunk("crevasse", chunk_placer::Crevasse::new(derive_seed(g.seed, "crevasse")));
 }
 
 pub fn ice_spikes(g: &mut BiomeBuilder) {
@@ -60,7 +63,7 @@ pub fn ice_spikes(g: &mut BiomeBuilder) {
   g.set_top_block(block![stone]);
 
   g.place("Ice spikes", PlacerStage::Tree, placer::IceSpikes::default());
-  g.place_chunk(chunk_placer::SnowOnStoneSurface::new(g.seed));
+  g.place_chunk("snow on stone", chunk_placer::SnowOnStoneSurface::new(g.seed));
 
   g.place("Snow", PlacerStage::Tree, placer::BetterTallerSnow::default());
 }
@@ -72,7 +75,7 @@ pub fn boulder_field(g: &mut BiomeBuilder) {
   g.color = "#6FAFCE";
   g.set_top_block(block![stone]);
 
-  g.place_chunk(chunk_placer::SnowOnStoneSurface::new(g.seed));
+  g.place_chunk("snow on stone", chunk_placer::SnowOnStoneSurface::new(g.seed));
   g.place("Snow", PlacerStage::Tree, placer::BetterTallerSnow::default());
 
   g.place(
@@ -90,7 +93,7 @@ pub fn alps(g: &mut BiomeBuilder) {
   g.add_layer(block![snow], 1, 2);
   g.add_layer(block![stone], 4, 5);
 
-  g.place_chunk(chunk_placer::SnowOnSnowSurface::new(g.seed));
+  g.place_chunk("snow on snow", chunk_placer::SnowOnSnowSurface::new(g.seed));
   g.place("Snow", PlacerStage::Tree, placer::BetterTallerSnow::default());
 }
 
@@ -100,7 +103,7 @@ pub fn frozen_peak(g: &mut BiomeBuilder) {
 
   g.set_top_block(block![stone]);
 
-  g.place_chunk(chunk_placer::SnowOnStoneSurface::new(g.seed));
+  g.place_chunk("snow on stone", chunk_placer::SnowOnStoneSurface::new(g.seed));
   g.place("Snow", PlacerStage::Tree, placer::BetterTallerSnow::default());
 }
 
@@ -113,13 +116,16 @@ pub fn deep_snow_beach(g: &mut BiomeBuilder) {
 
   g.place("Ice spikes", PlacerStage::Tree, placer::IceSpikes::default());
 
-  g.place_chunk(chunk_placer::SnowOnStoneSurface {
-    noise:       OpenSimplexNoise::new(g.seed),
-    a:           block![snow_layer],
-    add_snow:    2.25,
-    min_snow:    0,
-    place_above: block![stone].into(),
-  });
+  g.place_chunk(
+    "snow on stone",
+    chunk_placer::SnowOnStoneSurface {
+      noise:       OpenSimplexNoise::new(derive_seed(g.seed, "snow on stone")),
+      a:           block![snow_layer],
+      add_snow:    2.25,
+      min_snow:    0,
+      place_above: block![stone].into(),
+    },
+  );
 
   g.place(
     "gravel_patches",
@@ -10,9 +10,12 @@ pub fn blank(g: &mut BiomeBuilder) {
   g.set_top_block(block![stone]);
   g.set_underwater_block(block![stone]);
 
-  g.place_chunk(chunk_placer::CheckerboardSurface {
-    replace: block![stone].into(),
-    a:       block![concrete[color = "magenta"]],
-    b:       block![concrete[color = "black"]],
-  });
+  g.place_chunk(
+    "checkerboard",
+    chunk_placer::CheckerboardSurface {
+      replace: block![stone].into(),
+      a:       block![concrete[color = "magenta"]],
+      b:       block![concrete[color = "black"]],
+    },
+  );
 }
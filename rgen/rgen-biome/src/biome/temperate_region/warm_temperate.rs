@@ -1,5 +1,10 @@
 use rgen_base::{biome, block};
-use rgen_placer::placer;
+use rgen_placer::{
+  derive_seed,
+  noise::{OctavedNoise, OpenSimplexNoise, RemapNoise},
+  placer,
+};
+use rgen_spline::{Cosine, Spline};
 
 use crate::builder::PlacerStage;
 
@@ -7,6 +12,11 @@ use super::super::BiomeBuilder;
 
 // BIRCH
 
+/// Maps noise to the density of birch trees, so that there are clearings
+/// between the groves.
+static BIRCH_GROVES: Spline<&'static [(f64, f64)]> =
+  Spline::new(&[(0.0, 0.0), (0.35, 0.0), (0.55, 1.0), (1.0, 1.0)]);
+
 pub fn birch_river(g: &mut BiomeBuilder) {
   g.id = biome![birch_forest_hills];
   g.color = "#768A6A";
@@ -82,17 +92,22 @@ pub fn birch_woodland(g: &mut BiomeBuilder) {
       shroom:         block![rgen:polypore],
     },
   );
-  g.place(
+  // Dense groves of birch, with clearings in between.
+  g.place_with_density(
     "basic birch tree",
     PlacerStage::Tree,
     placer::BasicBirch {
       trunk:         block![log[variant = "birch"]],
       leaves:        block![leaves[variant = "birch"]],
-      avg_per_chunk: 12.0,
+      avg_per_chunk: 16.0,
       is_shrooms:    true,
       shroom:        block![rgen:polypore[type = "one"]],
       ground:        block![grass],
     },
+    RemapNoise::<_, _, Cosine>::new(
+      OctavedNoise::<OpenSimplexNoise, 3>::new(derive_seed(g.seed, "birch groves"), 1.0 / 64.0),
+      BIRCH_GROVES,
+    ),
   );
 
   g.place(
@@ -1,5 +1,8 @@
 use rgen_base::{Biome, BlockState, ChunkPos, Pos, block};
-use rgen_placer::{BiomeCachedChunk, ChunkPlacer, Placer, Random, Rng, grid::PointGrid};
+use rgen_placer::{
+  BiomeCachedChunk, ChunkPlacer, Placer, Random, Rng, derive_pos_seed, derive_seed,
+  grid::PointDistribution, noise::NoiseGenerator,
+};
 use rgen_world::PartialWorld;
 use smallvec::{SmallVec, smallvec};
 
@@ -24,7 +27,7 @@ pub struct BiomeBuilder {
   pub max_height: u32,
 
   // First pass placers. These run on multiple threads, and can only access a single chunk.
-  chunk_placers: Vec<Box<dyn ChunkPlacer>>,
+  chunk_placers: Vec<ChunkPlacerBuilder>,
 
   // Second pass placers. These all run on one thread, but can access the 8 surrounding chunks.
   placers: Vec<PlacerBuilder>,
@@ -36,15 +39,69 @@ pub struct Layer {
   pub max_depth: u32,
 }
 
+/// A noise map that controls how many placers are placed in an area. See
+/// [`BiomeBuilder::place_with_density`].
+pub type DensityMap = Box<dyn NoiseGenerator<Output = f64> + Send + Sync>;
+
 struct PlacerBuilder {
-  placer: Box<dyn Placer>,
+  placer:  Box<dyn Placer>,
+  name:    &'static str,
+  grid:    Box<dyn PointDistribution>,
+  density: Option<DensityMap>,
+
+  // The seed for this placer. This is derived from the world seed, biome name, and placer
+  // name, so that adding or removing placers won't change any other placers.
+  seed: u64,
+}
+
+pub(crate) struct ChunkPlacerBuilder {
+  placer: Box<dyn ChunkPlacer>,
   name:   &'static str,
-  grid:   PointGrid,
+
+  // Same as `PlacerBuilder::seed`.
+  seed: u64,
 }
 
 impl PlacerBuilder {
-  fn new(placer: Box<dyn Placer>, name: &'static str) -> Self {
-    Self { placer, name, grid: PointGrid::new() }
+  fn new(placer: Box<dyn Placer>, name: &'static str, parent_seed: u64) -> Self {
+    let grid = placer.distribution();
+    Self { placer, name, grid, density: None, seed: derive_seed(parent_seed, name) }
+  }
+
+  /// Returns true if a point at the given column should be placed, based on the
+  /// density map of this placer and the blend weight of the column.
+  fn accepts(&self, pos: Pos, blend_weight: f64) -> bool {
+    let density = match &self.density {
+      Some(density) => density.generate(pos.x as f64, pos.z as f64) * blend_weight,
+      None => blend_weight,
+    };
+    if density <= 0.0 {
+      return false;
+    } else if density >= 1.0 {
+      return true;
+    }
+
+    // Use a different seed than the placer itself, so that the placer's rng
+    // isn't correlated with the density check.
+    let mut rng = Rng::new(derive_pos_seed(derive_seed(self.seed, "density"), pos));
+    rng.range(0.0..1.0) < density
+  }
+}
+
+impl ChunkPlacerBuilder {
+  /// Creates a new chunk placer. The seed of the placer is derived from
+  /// `parent_seed` and `name`, so names must be unique for each parent seed.
+  pub fn new(placer: Box<dyn ChunkPlacer>, name: &'static str, parent_seed: u64) -> Self {
+    Self { placer, name, seed: derive_seed(parent_seed, name) }
+  }
+
+  /// Runs this placer on the given chunk. The placer is given a random number
+  /// generator unique to this placer and chunk.
+  pub fn place(&self, chunk: &mut BiomeCachedChunk, chunk_pos: ChunkPos) {
+    profile_scope!("chunk placer", self.name);
+
+    let mut rng = Rng::new(derive_pos_seed(self.seed, chunk_pos.min_block_pos()));
+    self.placer.place(chunk, &mut rng, chunk_pos);
   }
 }
 
@@ -90,46 +147,97 @@ impl BiomeBuilder {
 
   pub fn top_block(&self) -> BlockState { self.layers[0].state }
 
+  /// Adds a placer. Placers run `avg_per_chunk` times in each chunk, thinning
+  /// out by the blend weight towards the edges of the biome.
   pub fn place(&mut self, name: &'static str, stage: PlacerStage, placer: impl Placer + 'static) {
     self.place0(stage, name, Box::new(placer));
   }
 
+  /// Adds a placer, where the amount of placers in an area is controlled by
+  /// `density`. The density map is sampled at the X and Z block coordinates of
+  /// each placer, and is the chance that the placer will run there. So values
+  /// of 0 or less will leave clearings, and values of 1 or more will place as
+  /// many as `avg_per_chunk`.
+  ///
+  /// Like every placer, the density is also scaled by the blend weight of the
+  /// biome, so placers will thin out towards the edges of the biome.
+  ///
+  /// ```ignore
+  /// // Forests with clearings and dense groves.
+  /// g.place_with_density(
+  ///   "Oak Tree",
+  ///   PlacerStage::Tree,
+  ///   placer::BasicTree { avg_per_chunk: 8.0, ..Default::default() },
+  ///   OctavedNoise::<OpenSimplexNoise, 3>::new(g.seed, 1.0 / 64.0),
+  /// );
+  /// ```
+  pub fn place_with_density(
+    &mut self,
+    name: &'static str,
+    stage: PlacerStage,
+    placer: impl Placer + 'static,
+    density: impl NoiseGenerator<Output = f64> + Send + Sync + 'static,
+  ) {
+    self.place0(stage, name, Box::new(placer));
+    self.placers.last_mut().unwrap().density = Some(Box::new(density));
+  }
+
   // Don't monomorphise this.
   fn place0(&mut self, _stage: PlacerStage, name: &'static str, placer: Box<dyn Placer>) {
     // TODO: Using the stage, insert this at the right spot.
-    self.placers.push(PlacerBuilder::new(placer, name));
+    let duplicates = self.placers.iter().filter(|p| p.name == name).count();
+    self.placers.push(PlacerBuilder::new(placer, name, self.placer_seed(duplicates)));
   }
 
-  pub fn place_chunk(&mut self, placer: impl ChunkPlacer + 'static) {
-    self.chunk_placers.push(Box::new(placer));
+  pub fn place_chunk(&mut self, name: &'static str, placer: impl ChunkPlacer + 'static) {
+    self.place_chunk0(name, Box::new(placer));
   }
 
-  pub fn generate(&self, rng: &mut Rng, chunk: &mut BiomeCachedChunk, chunk_pos: ChunkPos) {
+  // Don't monomorphise this.
+  fn place_chunk0(&mut self, name: &'static str, placer: Box<dyn ChunkPlacer>) {
+    let duplicates = self.chunk_placers.iter().filter(|p| p.name == name).count();
+    self.chunk_placers.push(ChunkPlacerBuilder::new(placer, name, self.placer_seed(duplicates)));
+  }
+
+  // The seed that placers in this biome derive their seeds from. Placers with
+  // the same name are told apart by the order they were added in.
+  fn placer_seed(&self, duplicates: usize) -> u64 {
+    let seed = derive_seed(self.seed, self.name);
+    if duplicates == 0 { seed } else { Rng::new(seed ^ duplicates as u64).next() }
+  }
+
+  pub fn generate(&self, chunk: &mut BiomeCachedChunk, chunk_pos: ChunkPos) {
     profile_scope!("generate biome", self.name);
 
     for placer in self.chunk_placers.iter() {
-      placer.place(chunk, rng, chunk_pos);
+      placer.place(chunk, chunk_pos);
     }
   }
 
-  /// Decorates the given chunk. The `rng` passed in should only be seeded with
-  /// the world seed.
+  /// Decorates the given chunk. Each placer is seeded from the world seed, the
+  /// name of this biome, and the name of the placer.
+  ///
+  /// `blend_weight` is called for every column a placer lands on, so it should
+  /// be cached by the caller.
   pub fn decorate(
     &self,
-    rng: &mut Rng,
     chunk_pos: ChunkPos,
     world: &mut PartialWorld,
     is_in_chunk: impl Fn(Pos) -> bool,
+    blend_weight: impl Fn(Pos) -> f64,
   ) {
     profile_scope!("decorate biome", self.name);
 
     for placer in self.placers.iter() {
       profile_scope!("placer", placer.name);
 
-      let seed = rng.next();
+      let seed = placer.seed;
 
+      // Scale the grid so that each chunk covers enough squares to end up with
+      // `avg_per_chunk` points.
       const SCALE: f64 = 1.0 / 16.0;
-      let scale = SCALE * placer.placer.avg_per_chunk().powf(0.5);
+      let squares_per_chunk = placer.placer.avg_per_chunk() / placer.grid.points_per_square();
+      let scale = SCALE * squares_per_chunk.sqrt();
 
       let min_x = chunk_pos.min_block_pos().x as f64 * scale;
       let min_y = chunk_pos.min_block_pos().z as f64 * scale;
@@ -137,21 +245,31 @@ impl BiomeBuilder {
       let max_y = (chunk_pos.min_block_pos().z + 15) as f64 * scale;
 
       for point in placer.grid.points_in_area(seed, min_x, min_y, max_x, max_y) {
-        let pos = Pos::new((point.0 / scale) as i32, 0, (point.1 / scale) as i32);
+        let column = Pos::new((point.0 / scale) as i32, 0, (point.1 / scale) as i32);
+
+        // The density map and blend weight are only checked once a surface in this
+        // column is in this biome, as most points are in other biomes, and sampling
+        // them is expensive.
+        let mut accepted = None;
+
         // NB: Assume the surfaces won't change in `world.attempt`, and that re-fetching
         // the surfaces is effectively free (which it should be).
         let mut i = 0;
-        while let Some(surface) = world.surfaces(pos).get(i) {
-          let pos = pos.with_y(*surface as i32 + 1);
-
-          if is_in_chunk(pos) {
-            // This builds a unique seed for each placer. This gives the placer the same
-            // seed if it crosses chunk boundaries.
-            let seed = rng.next() ^ (pos.x as u64) << 32 ^ pos.z as u64;
-            world.attempt(|world| placer.placer.place(world, &mut Rng::new(seed), pos));
+        while let Some(surface) = world.surfaces(column).get(i) {
+          let pos = column.with_y(*surface as i32 + 1);
+          i += 1;
+
+          if !is_in_chunk(pos) {
+            continue;
+          }
+          if !*accepted.get_or_insert_with(|| placer.accepts(column, blend_weight(column))) {
+            break;
           }
 
-          i += 1;
+          // This builds a unique seed for each placer. This gives the placer the same
+          // seed if it crosses chunk boundaries.
+          let seed = derive_pos_seed(seed, pos);
+          world.attempt(|world| placer.placer.place(world, &mut Rng::new(seed), pos));
         }
       }
     }
@@ -0,0 +1,226 @@
+//! Aquifers decide which fluid caves are filled with.
+//!
+//! The world is split up into large regions, each of which may have a body of
+//! water or lava with its own level. Caves carved below that level are filled
+//! with the fluid instead of air.
+//!
+//! Fluids are walled off with stone where they meet something else, so that
+//! carving never leaves a wall of floating water:
+//! - Columns under the sea (or a river) fill caves with water up to sea level,
+//!   and the columns next to them can't be carved below sea level.
+//! - The edges of each region can't be carved below the fluid level of that
+//!   region.
+
+use rgen_base::{ChunkPos, ChunkRelPos, Pos, StateId, block};
+use rgen_placer::{
+  FluidLevel, derive_seed,
+  noise::{NoiseGenerator, OctavedNoise, OpenSimplexNoise, SeededNoise, WorleyNoise},
+};
+use rgen_world::BlockInfoSupplier;
+
+use crate::{WorldBiomes, terrain::ChunkTerrain};
+
+/// The top of the sea. Matches the water placed in `generate_stone`.
+const SEA_LEVEL: i32 = 63;
+
+/// The width of the stone walls around the edge of each region, in blocks.
+const RIM_WIDTH: f64 = 3.0;
+
+pub struct Aquifer {
+  regions:   WorleyNoise,
+  level_map: OctavedNoise<OpenSimplexNoise, 2>,
+  lava_map:  OctavedNoise<OpenSimplexNoise, 2>,
+}
+
+/// What a carver should do with a single block.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Fill {
+  /// The block can be carved into air.
+  Air,
+  /// The block should be carved, and replaced with this fluid.
+  Fluid(StateId),
+  /// The block is holding back a fluid, so it must not be carved.
+  Solid,
+}
+
+/// The aquifer of every column in a chunk.
+pub struct ChunkAquifer {
+  chunk_pos: ChunkPos,
+
+  /// Indexed by `[x][z]`.
+  columns: [[AquiferColumn; 16]; 16],
+}
+
+#[derive(Clone, Copy)]
+struct AquiferColumn {
+  fluid:   Option<(FluidLevel, StateId)>,
+  /// Blocks at or below this level can't be carved.
+  barrier: i32,
+}
+
+impl Aquifer {
+  pub fn new(seed: u64) -> Self {
+    Aquifer {
+      regions:   WorleyNoise::new(derive_seed(seed, "regions")).with_freq(1.0 / 96.0),
+      level_map: OctavedNoise::new(derive_seed(seed, "level"), 1.0 / 512.0),
+      lava_map:  OctavedNoise::new(derive_seed(seed, "lava"), 1.0 / 512.0),
+    }
+  }
+
+  /// Returns the fluid level of the region the given column is in, ignoring
+  /// the sea.
+  fn region_fluid(&self, pos: Pos) -> (Option<FluidLevel>, f64) {
+    let region = self.regions.generate(pos.x as f64, pos.z as f64);
+
+    // Sample at the center of the region, so the whole region has the same level.
+    let (cx, cz) = region.center;
+    let level = self.level_map.generate(cx, cz) * 0.5 + 0.5;
+    let lava = self.lava_map.generate(cx, cz) * 0.5 + 0.5;
+
+    let edge = region.edge();
+
+    // A bit under half the regions are dry.
+    if level < 0.4 {
+      return (None, edge);
+    }
+
+    let level = 12 + ((level - 0.4) / 0.6 * 44.0) as i32;
+    let fluid = if lava > 0.7 && level < 32 { block![lava] } else { block![water] };
+
+    (Some(FluidLevel { level, fluid }), edge)
+  }
+
+  pub fn chunk(
+    &self,
+    world: &WorldBiomes,
+    terrain: &ChunkTerrain,
+    info: &BlockInfoSupplier,
+    chunk_pos: ChunkPos,
+  ) -> ChunkAquifer {
+    profile_function!();
+
+    // Find the sea columns in this chunk, and the columns directly around it.
+    // Only the border needs to be sampled, as the terrain has the heights inside
+    // the chunk cached.
+    let mut sea = [[false; 18]; 18];
+    for (x, row) in sea.iter_mut().enumerate() {
+      for (z, sea) in row.iter_mut().enumerate() {
+        let pos = chunk_pos.min_block_pos() + Pos::new(x as i32 - 1, 0, z as i32 - 1);
+        let height = terrain.max_height(pos).unwrap_or_else(|| world.sample_height(pos));
+        *sea = height < 64.0;
+      }
+    }
+
+    let water = info.encode(block![water]);
+    let mut columns = [[AquiferColumn { fluid: None, barrier: i32::MIN }; 16]; 16];
+    for (x, row) in columns.iter_mut().enumerate() {
+      for (z, column) in row.iter_mut().enumerate() {
+        let (sx, sz) = (x + 1, z + 1);
+        if sea[sx][sz] {
+          let fluid = FluidLevel { level: SEA_LEVEL, fluid: block![water] };
+          *column = AquiferColumn { fluid: Some((fluid, water)), barrier: i32::MIN };
+          continue;
+        }
+
+        let pos = chunk_pos.min_block_pos() + Pos::new(x as i32, 0, z as i32);
+        let (fluid, edge) = self.region_fluid(pos);
+
+        let mut barrier = match fluid {
+          Some(fluid) if edge < RIM_WIDTH => fluid.level,
+          _ => i32::MIN,
+        };
+        if sea[sx - 1][sz] || sea[sx + 1][sz] || sea[sx][sz - 1] || sea[sx][sz + 1] {
+          barrier = barrier.max(SEA_LEVEL);
+        }
+
+        *column = AquiferColumn { fluid: fluid.map(|f| (f, info.encode(f.fluid))), barrier };
+      }
+    }
+
+    ChunkAquifer { chunk_pos, columns }
+  }
+}
+
+impl ChunkAquifer {
+  /// Returns the fluid level of the given column.
+  pub fn fluid_level(&self, pos: ChunkRelPos) -> Option<FluidLevel> {
+    self.columns[pos.x() as usize][pos.z() as usize].fluid.map(|(f, _)| f)
+  }
+
+  /// Returns what should happen to the given block when it is carved. The
+  /// position must be within this chunk.
+  pub fn fill(&self, pos: Pos) -> Fill {
+    debug_assert!(pos.in_chunk(self.chunk_pos));
+
+    let rel = pos.chunk_rel();
+    let column = &self.columns[rel.x() as usize][rel.z() as usize];
+
+    if pos.y <= column.barrier {
+      return Fill::Solid;
+    }
+
+    match column.fluid {
+      Some((level, fluid)) if level.contains(pos.y) => Fill::Fluid(fluid),
+      _ => Fill::Air,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rgen_base::Chunk;
+  use rgen_world::Context;
+
+  use super::*;
+
+  #[test]
+  fn no_floating_water() {
+    let ctx = Context::new_test(1234);
+    let world = WorldBiomes::new(&ctx.blocks, ctx.seed);
+    let water = ctx.blocks.encode(block![water]);
+
+    // Find some chunks on a coastline.
+    let coast = (0..256)
+      .map(|x| ChunkPos::new(x, 0))
+      .filter(|chunk_pos| {
+        let heights = [(0, 0), (15, 0), (0, 15), (15, 15)]
+          .map(|(x, z)| world.sample_height(chunk_pos.min_block_pos() + Pos::new(x, 0, z)));
+        heights.iter().any(|&h| h < 64.0) && heights.iter().any(|&h| h >= 64.0)
+      })
+      .take(4)
+      .collect::<Vec<_>>();
+    assert!(!coast.is_empty());
+
+    for chunk_pos in coast {
+      let terrain = ChunkTerrain::new(&world, chunk_pos);
+      let mut chunk = Chunk::new();
+      world.generate_stone(&ctx, &terrain, &mut chunk, chunk_pos);
+      let stone = chunk.clone();
+
+      let aquifer = world.aquifer.chunk(&world, &terrain, &ctx.blocks, chunk_pos);
+      world.cave.carve(&world, &aquifer, &mut chunk, chunk_pos);
+
+      for x in 1..15 {
+        for z in 1..15 {
+          for y in 1..255 {
+            let rel = ChunkRelPos::new(x, y, z);
+            if chunk.get(rel) != StateId::AIR || stone.get(rel) == StateId::AIR {
+              continue;
+            }
+
+            // A carved out block must not have water beside it or above it.
+            for (dx, dy, dz) in [(-1, 0, 0), (1, 0, 0), (0, 0, -1), (0, 0, 1), (0, 1, 0)] {
+              let neighbor = ChunkRelPos::new((x as i32 + dx) as u8, y + dy, (z as i32 + dz) as u8);
+              assert!(
+                chunk.get(neighbor) != water,
+                "floating water next to {:?} in chunk {:?}",
+                rel,
+                chunk_pos
+              );
+            }
+          }
+        }
+      }
+    }
+  }
+}
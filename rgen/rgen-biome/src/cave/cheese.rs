@@ -1,28 +1,46 @@
-use rgen_base::{Chunk, ChunkPos, Pos, StateId, block};
+use rgen_base::{Chunk, ChunkPos, Pos, StateId};
 use rgen_placer::noise::{NoiseGenerator3D, OctavedNoise, PerlinNoise};
+use rgen_spline::Linear;
 use rgen_world::BlockInfoSupplier;
 
+use super::{Carver, CarverConfig, ChunkAquifer};
 use crate::WorldBiomes;
 
 /// Cheese caves are the big caverns.
+///
+/// The `frequency` of the config is the frequency of the cave noise, and the
+/// `radius` spline maps the Y level (from the bottom to the top of `height`)
+/// to how open the caves are at that level.
 pub struct CheeseCarver {
+  config:   CarverConfig,
   cave_map: OctavedNoise<PerlinNoise, 3>,
 
-  water: StateId,
+  fluid: StateId,
 }
 
 impl CheeseCarver {
-  pub fn new(info: &BlockInfoSupplier, seed: u64) -> Self {
+  pub fn new(info: &BlockInfoSupplier, seed: u64, config: CarverConfig) -> Self {
     CheeseCarver {
-      cave_map: OctavedNoise::new(seed, 1.0 / 64.0),
+      cave_map: OctavedNoise::new(seed, config.frequency),
 
-      water: info.encode(block![water]),
+      fluid: info.encode(config.fluid),
+      config,
     }
   }
+}
 
-  pub fn carve(&self, world: &WorldBiomes, chunk: &mut Chunk, chunk_pos: ChunkPos) {
+impl Carver for CheeseCarver {
+  fn carve(
+    &self,
+    world: &WorldBiomes,
+    aquifer: &ChunkAquifer,
+    chunk: &mut Chunk,
+    chunk_pos: ChunkPos,
+  ) {
     profile_function!();
 
+    let (min_y, max_y) = (*self.config.height.start(), *self.config.height.end());
+
     for rel_x in 0..16_u8 {
       for rel_z in 0..16_u8 {
         let pos = chunk_pos.min_block_pos() + Pos::new(rel_x.into(), 0, rel_z.into());
@@ -33,7 +51,7 @@ impl CheeseCarver {
         // The closer to the river we are, the higher this number is.
         let river_closeness = 1.0 - world.sample_river_distance(pos);
 
-        for y in 0..=height as i32 {
+        for y in min_y.max(0)..=max_y.min(height as i32) {
           let pos = pos.with_y(y);
           let noise =
             self.cave_map.generate_3d(pos.x as f64, pos.y as f64 * 4.0, pos.z as f64) * 0.5 + 0.5;
@@ -59,34 +77,11 @@ impl CheeseCarver {
           };
           let river_modifier = 1.0 - river_modifier;
 
-          if noise < 0.3 * surface_modifier * bedrock_modifier * river_modifier {
-            let mut near_water = false;
-            for offset in [
-              Pos::new(0, 0, 0),
-              Pos::new(-1, 0, 0),
-              Pos::new(1, 0, 0),
-              Pos::new(0, 0, -1),
-              Pos::new(0, 0, 1),
-              Pos::new(0, 1, 0),
-              Pos::new(0, -1, 0),
-            ] {
-              let pos = pos + offset;
-              // Chunk borders: we don't care! We can let a bit of floating water exist.
-              if !pos.in_chunk(chunk_pos) {
-                continue;
-              }
-
-              let block = chunk.get(pos.chunk_rel());
-
-              if block == self.water {
-                near_water = true;
-                break;
-              }
-            }
-
-            if !near_water {
-              chunk.set(pos.chunk_rel(), StateId::AIR);
-            }
+          let openness =
+            self.config.radius.sample::<Linear>((y - min_y) as f64 / (max_y - min_y).max(1) as f64);
+
+          if noise < openness * surface_modifier * bedrock_modifier * river_modifier {
+            self.config.carve(aquifer, chunk, pos, self.fluid);
           }
         }
       }
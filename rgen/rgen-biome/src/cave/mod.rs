@@ -1,27 +1,122 @@
+mod aquifer;
 mod cheese;
 mod noodle;
 
+use std::ops::RangeInclusive;
+
 use cheese::CheeseCarver;
 use noodle::NoodleCarver;
-use rgen_base::{Chunk, ChunkPos};
+use rgen_base::{BlockState, Chunk, ChunkPos, Pos, StateId, block};
+use rgen_placer::derive_seed;
+use rgen_spline::Spline;
 use rgen_world::BlockInfoSupplier;
 
 use crate::WorldBiomes;
 
+pub use aquifer::{Aquifer, ChunkAquifer, Fill};
+
+/// Carves caves out of a chunk, after the stone has been generated, and before
+/// the top layer is placed.
+///
+/// Carvers are run in the order they were registered with
+/// [`WorldBiomes::add_carver`]. A carver must produce the same blocks in a
+/// chunk regardless of which chunks have been generated before it, so any
+/// randomness should come from the world seed and block positions only.
+///
+/// Carvers should check the `aquifer` before carving each block, so that caves
+/// are filled with the right fluid. See [`CarverConfig::carve`].
+pub trait Carver: Send + Sync {
+  fn carve(
+    &self,
+    world: &WorldBiomes,
+    aquifer: &ChunkAquifer,
+    chunk: &mut Chunk,
+    chunk_pos: ChunkPos,
+  );
+}
+
+/// Settings shared by all the carvers. Each carver documents how it uses these.
+pub struct CarverConfig {
+  /// How often caves are carved. For carvers that start individual caves, this
+  /// is the average number of caves starting in each chunk.
+  pub frequency: f64,
+
+  /// The Y levels this carver is allowed to carve.
+  pub height: RangeInclusive<i32>,
+
+  /// Maps a value in 0..=1 to the size of a cave. The input depends on the
+  /// carver.
+  pub radius: Spline<&'static [(f64, f64)]>,
+
+  /// Carved blocks at or below this level are filled with `fluid` instead of
+  /// air, unless the aquifer has its own fluid there.
+  pub fluid_level: i32,
+  pub fluid:       BlockState,
+}
+
+impl CarverConfig {
+  /// Carves out the block at `pos`, using the aquifer to pick the fluid it is
+  /// replaced with. `fluid` must be the encoded [`CarverConfig::fluid`].
+  pub fn carve(&self, aquifer: &ChunkAquifer, chunk: &mut Chunk, pos: Pos, fluid: StateId) {
+    let block = match aquifer.fill(pos) {
+      Fill::Solid => return,
+      Fill::Fluid(fluid) => fluid,
+      Fill::Air if pos.y <= self.fluid_level => fluid,
+      Fill::Air => StateId::AIR,
+    };
+
+    chunk.set(pos.chunk_rel(), block);
+  }
+}
+
+/// All the carvers in a world.
 pub struct CaveCarver {
-  noodle: NoodleCarver,
-  cheese: CheeseCarver,
+  carvers: Vec<Box<dyn Carver>>,
 }
 
 impl CaveCarver {
   pub fn new(info: &BlockInfoSupplier, seed: u64) -> Self {
-    CaveCarver { noodle: NoodleCarver::new(info, seed), cheese: CheeseCarver::new(info, seed) }
+    let mut carver = CaveCarver { carvers: vec![] };
+
+    carver.add(NoodleCarver::new(
+      info,
+      derive_seed(seed, "noodle"),
+      CarverConfig {
+        frequency:   1.0 / 9.0,
+        height:      0..=255,
+        radius:      Spline::new(&[(0.0, 1.0), (1.0, 5.0)]),
+        fluid_level: 10,
+        fluid:       block![lava],
+      },
+    ));
+    carver.add(CheeseCarver::new(
+      info,
+      derive_seed(seed, "cheese"),
+      CarverConfig {
+        frequency:   1.0 / 64.0,
+        height:      0..=255,
+        radius:      Spline::new(&[(0.0, 0.3), (1.0, 0.3)]),
+        fluid_level: 10,
+        fluid:       block![lava],
+      },
+    ));
+
+    carver
   }
 
-  pub fn carve(&self, world: &WorldBiomes, chunk: &mut Chunk, pos: ChunkPos) {
+  pub fn add(&mut self, carver: impl Carver + 'static) { self.carvers.push(Box::new(carver)); }
+
+  pub fn carve(
+    &self,
+    world: &WorldBiomes,
+    aquifer: &ChunkAquifer,
+    chunk: &mut Chunk,
+    pos: ChunkPos,
+  ) {
     profile_function!();
 
-    self.noodle.carve(chunk, pos);
-    self.cheese.carve(world, chunk, pos);
+    for carver in &self.carvers {
+      carver.carve(world, aquifer, chunk, pos);
+    }
   }
 }
@@ -1,18 +1,32 @@
-use rgen_base::{Chunk, ChunkPos, Pos, StateId, block};
+use rgen_base::{Chunk, ChunkPos, Pos, StateId};
 use rgen_placer::{
+  derive_seed,
   grid::PointGrid,
   noise::{NoiseGenerator3D, OctavedNoise, PerlinNoise},
 };
+use rgen_spline::Linear;
 use rgen_world::BlockInfoSupplier;
 
+use super::{Carver, CarverConfig, ChunkAquifer};
+use crate::WorldBiomes;
+
 /// Noodle caves are the long thin tunnels, the "normal" caves.
+///
+/// The `radius` spline of the config maps the radius noise to the radius of the
+/// tunnel, in blocks.
 pub struct NoodleCarver {
-  seed: u64,
-  grid: PointGrid,
+  seed:   u64,
+  config: CarverConfig,
+  grid:   PointGrid,
+
+  /// The distance between each cave origin in the grid.
+  scale:         f64,
+  /// How far away from a chunk a cave can start and still reach into it.
+  search_radius: i32,
 
   density_map: OctavedNoise<PerlinNoise, 2>,
 
-  water: StateId,
+  fluid: StateId,
 }
 
 #[derive(Clone)]
@@ -33,38 +47,44 @@ struct NoodleCave<'a> {
   direction: f64,
 }
 
-const CAVE_RADIUS: i32 = 96;
-// FIXME: This needs to be as much as 30 blocks smaller than the radius to avoid
-// all the chunk border artifacts. Need to figure out whats going on with this.
-const MAX_CAVE_AREA: f64 = CAVE_RADIUS as f64 - 4.0;
+/// The furthest a cave can wander from its origin, on the X and Z axes.
+const MAX_CAVE_AREA: f64 = 92.0;
+
+/// The Y level caves start at, if it is within the height of the carver.
+const ORIGIN_Y: i32 = 32;
 
 impl NoodleCarver {
-  pub fn new(info: &BlockInfoSupplier, seed: u64) -> Self {
+  pub fn new(info: &BlockInfoSupplier, seed: u64, config: CarverConfig) -> Self {
+    // The point grid places one point per unit on average, so scale it such that
+    // there are `frequency` points in each chunk.
+    let scale = 16.0 / config.frequency.sqrt();
+
+    let max_radius = config.radius.storage.iter().map(|&(_, r)| r).fold(0.0, f64::max);
+    let search_radius = (MAX_CAVE_AREA + max_radius).ceil() as i32 + 1;
+
     NoodleCarver {
       seed,
 
       grid: PointGrid::new(),
-      density_map: OctavedNoise::new(seed, 1.0 / 16.0),
+      scale,
+      search_radius,
+      density_map: OctavedNoise::new(derive_seed(seed, "density"), 1.0 / 16.0),
 
-      water: info.encode(block![water]),
+      fluid: info.encode(config.fluid),
+      config,
     }
   }
+}
 
-  pub fn carve(&self, chunk: &mut Chunk, chunk_pos: ChunkPos) {
+impl Carver for NoodleCarver {
+  fn carve(&self, _: &WorldBiomes, aquifer: &ChunkAquifer, chunk: &mut Chunk, chunk_pos: ChunkPos) {
     profile_function!();
 
-    let scale = 48.0;
-
-    let min_pos = chunk_pos.min_block_pos();
-    let cave_min_x = ((min_pos.x - CAVE_RADIUS) as f64) / scale;
-    let cave_min_z = ((min_pos.z - CAVE_RADIUS) as f64) / scale;
-    let cave_max_x = ((min_pos.x + 16 + CAVE_RADIUS) as f64) / scale;
-    let cave_max_z = ((min_pos.z + 16 + CAVE_RADIUS) as f64) / scale;
+    let origin_y = ORIGIN_Y.clamp(*self.config.height.start(), *self.config.height.end()) as f64;
 
-    let points =
-      self.grid.points_in_area(self.seed, cave_min_x, cave_min_z, cave_max_x, cave_max_z);
+    let points = self.grid.points_near_chunk(self.seed, chunk_pos, self.scale, self.search_radius);
     for point in points {
-      let pos = ((point.0 * scale), 32.0, (point.1 * scale));
+      let pos = (point.0, origin_y, point.1);
 
       // A seed unique to this cave.
       let cave_seed = self.seed
@@ -75,10 +95,10 @@ impl NoodleCarver {
         carver: self,
         pos,
         origin: pos,
-        radius_map: OctavedNoise::new(cave_seed, 1.0 / 64.0),
-        delta_x_map: OctavedNoise::new(cave_seed.wrapping_add(1), 1.0 / 64.0),
-        delta_y_map: OctavedNoise::new(cave_seed.wrapping_add(2), 1.0 / 64.0),
-        delta_z_map: OctavedNoise::new(cave_seed.wrapping_add(3), 1.0 / 64.0),
+        radius_map: OctavedNoise::new(derive_seed(cave_seed, "radius"), 1.0 / 64.0),
+        delta_x_map: OctavedNoise::new(derive_seed(cave_seed, "delta x"), 1.0 / 64.0),
+        delta_y_map: OctavedNoise::new(derive_seed(cave_seed, "delta y"), 1.0 / 64.0),
+        delta_z_map: OctavedNoise::new(derive_seed(cave_seed, "delta z"), 1.0 / 64.0),
         direction: 1.0,
       };
 
@@ -86,12 +106,12 @@ impl NoodleCarver {
       cave2.direction = -1.0;
 
       for _ in 0..100 {
-        if cave.dig(chunk, chunk_pos) {
+        if cave.dig(aquifer, chunk, chunk_pos) {
           break;
         }
       }
       for _ in 0..100 {
-        if cave2.dig(chunk, chunk_pos) {
+        if cave2.dig(aquifer, chunk, chunk_pos) {
           break;
         }
       }
@@ -101,10 +121,11 @@ impl NoodleCarver {
 
 impl NoodleCave<'_> {
   fn radius(&self) -> f64 {
-    (self.radius_map.generate_3d(self.pos.0, self.pos.1, self.pos.2) * 0.5 + 0.5) * 4.0 + 1.0
+    let noise = self.radius_map.generate_3d(self.pos.0, self.pos.1, self.pos.2) * 0.5 + 0.5;
+    self.carver.config.radius.sample::<Linear>(noise.clamp(0.0, 1.0))
   }
 
-  fn dig(&mut self, chunk: &mut Chunk, chunk_pos: ChunkPos) -> bool {
+  fn dig(&mut self, aquifer: &ChunkAquifer, chunk: &mut Chunk, chunk_pos: ChunkPos) -> bool {
     let dx = self.delta_x_map.generate_3d(self.pos.0, self.pos.1, self.pos.2) * self.direction;
     let dy = self.delta_y_map.generate_3d(self.pos.0, self.pos.1, self.pos.2) * self.direction;
     let dz = self.delta_z_map.generate_3d(self.pos.0, self.pos.1, self.pos.2) * self.direction;
@@ -113,11 +134,13 @@ impl NoodleCave<'_> {
 
     let radius = self.radius();
 
-    self.dig_delta(chunk_pos, chunk, radius, dx, dy, dz)
+    self.dig_delta(aquifer, chunk_pos, chunk, radius, dx, dy, dz)
   }
 
+  #[allow(clippy::too_many_arguments)]
   fn dig_delta(
     &mut self,
+    aquifer: &ChunkAquifer,
     chunk_pos: ChunkPos,
     chunk: &mut Chunk,
     radius: f64,
@@ -133,7 +156,8 @@ impl NoodleCave<'_> {
       self.pos.1 += dy;
       self.pos.2 += dz;
 
-      if self.pos.1 < 0.0 || self.pos.1 > 256.0 {
+      let height = &self.carver.config.height;
+      if self.pos.1 < *height.start() as f64 || self.pos.1 > *height.end() as f64 + 1.0 {
         return true;
       }
       if (self.pos.0 - self.origin.0).abs() > MAX_CAVE_AREA
@@ -148,12 +172,12 @@ impl NoodleCave<'_> {
       let max = chunk_pos.min_block_pos() + Pos::new(max_radius + 15, 0, max_radius + 15);
 
       if pos.x < min.x || pos.x > max.x || pos.z < min.z || pos.z > max.z {
-        // Skip this iteration. We're far enough away from the chunk that we don't need
-        // to bother looping below.
-        return false;
+        // Skip this step. We're far enough away from the chunk that we don't need
+        // to bother looping below. Note that this must not stop the cave, as the
+        // path of the cave needs to be the same in every chunk.
+        continue;
       }
 
-      let mut hit_water = false;
       for y in -max_radius..=max_radius {
         for z in -max_radius..=max_radius {
           for x in -max_radius..=max_radius {
@@ -174,44 +198,12 @@ impl NoodleCave<'_> {
                   + 0.6;
 
               if density > dist_to_center {
-                // TODO: This is pretty dumb. Maybe add a concept of "near water" so we can skip
-                // this sometimes?
-                let mut near_water = false;
-                for offset in [
-                  Pos::new(-1, 0, 0),
-                  Pos::new(1, 0, 0),
-                  Pos::new(0, 0, -1),
-                  Pos::new(0, 0, 1),
-                  Pos::new(0, 1, 0),
-                  Pos::new(0, -1, 0),
-                ] {
-                  let pos = pos + offset;
-                  // Chunk borders: we don't care! We can let a bit of floating water exist.
-                  if !pos.in_chunk(chunk_pos) {
-                    continue;
-                  }
-
-                  let block = chunk.get(pos.chunk_rel());
-
-                  if block == self.carver.water {
-                    near_water = true;
-                    break;
-                  }
-                }
-
-                if near_water {
-                  hit_water = true;
-                } else {
-                  chunk.set(pos.chunk_rel(), StateId::AIR);
-                }
+                self.carver.config.carve(aquifer, chunk, pos, self.carver.fluid);
               }
             }
           }
         }
       }
-      if hit_water {
-        return true;
-      }
     }
     false
   }
@@ -12,6 +12,9 @@ pub const DEBUG_ORES: bool = false;
 /// Enables village generation.
 pub const VILLAGES: bool = false;
 
+/// Enables dungeon generation.
+pub const DUNGEONS: bool = false;
+
 /// Overrides the geographic type of the world.
 pub const GEOGRAPHIC_TYPE_OVERRIDE: Option<GeographicType> = None;
 
@@ -22,5 +25,14 @@ pub const CLIMATE_TYPE_OVERRIDE: Option<ClimateType> = None;
 /// table.rs line 37 to set new biome
 pub const BIOME_OVERRIDE: bool = false;
 
+/// Blends the edges of surface biomes together, instead of cutting them off in
+/// a straight line.
+pub const BIOME_BLENDING: bool = true;
+
+/// Samples the 3D density map on a coarse lattice, and interpolates between
+/// it, instead of sampling every block. This is much faster, but the terrain
+/// is a bit smoother.
+pub const INTERPOLATED_DENSITY: bool = false;
+
 /// Replaces everything with a superflat world.
 pub const SUPERFLAT: bool = false;
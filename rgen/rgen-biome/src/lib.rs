@@ -1,7 +1,9 @@
-use cave::CaveCarver;
+use std::cell::Cell;
+
+use cave::{Aquifer, CaveCarver, ChunkAquifer};
 use rgen_base::{Chunk, ChunkPos, ChunkRelPos, Pos, StateId, block};
 use rgen_placer::{
-  BiomeCachedChunk, BiomeColumn, ChunkPlacer, Rng, TemporaryBiome, chunk_placer,
+  BiomeCachedChunk, BiomeColumn, CaveSpans, TemporaryBiome, chunk_placer, derive_seed,
   noise::{
     NoiseGenerator, NoiseGenerator3D, OctavedNoise, OpenSimplexNoise, PerlinNoise, SeededNoise,
     ShiftedNoise, VoronoiNoise,
@@ -9,8 +11,10 @@ use rgen_placer::{
 };
 use rgen_spline::{Cosine, Spline};
 use rgen_world::{BlockInfoSupplier, Context, Generator, PartialWorld};
+use smallvec::SmallVec;
 use structure::StructureGenerator;
 use table::CompositionLookup;
+use terrain::ChunkTerrain;
 
 mod biome;
 mod builder;
@@ -19,8 +23,13 @@ mod feature;
 mod lookup;
 mod structure;
 mod table;
+mod terrain;
 
 pub use builder::BiomeBuilder;
+use builder::ChunkPlacerBuilder;
+pub use cave::{Carver, CarverConfig};
+pub use structure::{LootTable, StructureType};
+pub use table::{ClimateType, GeographicType, UnknownTypeError};
 
 #[macro_use]
 extern crate puffin;
@@ -35,11 +44,17 @@ pub struct WorldBiomes {
   composition_lookup: CompositionLookup,
 
   cave:      CaveCarver,
+  aquifer:   Aquifer,
   structure: StructureGenerator,
 
   temperature_map: OctavedNoise<PerlinNoise, 8>,
   humidity_map:    OctavedNoise<PerlinNoise, 8>,
 
+  /// 3D noise maps which shift the climate of cave biomes away from the
+  /// surface climate.
+  cave_temperature_map: OctavedNoise<PerlinNoise, 3>,
+  cave_humidity_map:    OctavedNoise<PerlinNoise, 3>,
+
   /// Defines how far inland or how far into the sea any given block is.
   ///
   /// In order:
@@ -83,7 +98,7 @@ pub struct WorldBiomes {
   /// Controlls the depth of the sub layer (usually dirt).
   sub_layer_map: OctavedNoise<OpenSimplexNoise, 3>,
 
-  global_chunk_placers: Vec<Box<dyn ChunkPlacer>>,
+  global_chunk_placers: Vec<ChunkPlacerBuilder>,
 }
 
 lazy_static::lazy_static! {
@@ -140,73 +155,135 @@ impl WorldBiomes {
 
       composition_lookup: CompositionLookup::new(seed),
 
-      cave: CaveCarver::new(info, seed),
-      structure: StructureGenerator::new(seed),
+      cave: CaveCarver::new(info, derive_seed(seed, "caves")),
+      aquifer: Aquifer::new(derive_seed(seed, "aquifer")),
+      structure: StructureGenerator::new(derive_seed(seed, "structures")),
+
+      temperature_map: OctavedNoise::new(derive_seed(seed, "temperature"), 1.0 / 2048.0),
+      humidity_map: OctavedNoise::new(derive_seed(seed, "humidity"), 1.0 / 4096.0),
 
-      temperature_map: OctavedNoise::new(seed, 1.0 / 2048.0),
-      humidity_map: OctavedNoise::new(seed, 1.0 / 4096.0),
+      cave_temperature_map: OctavedNoise::new(derive_seed(seed, "cave temperature"), 1.0 / 256.0),
+      cave_humidity_map: OctavedNoise::new(derive_seed(seed, "cave humidity"), 1.0 / 256.0),
 
-      continentalness_map: OctavedNoise::new(seed, 1.0 / 1024.0),
-      peaks_valleys_map: OctavedNoise::new(seed, 1.0 / 256.0),
-      erosion_map: OctavedNoise::new(seed, 1.0 / 2048.0),
+      continentalness_map: OctavedNoise::new(derive_seed(seed, "continentalness"), 1.0 / 1024.0),
+      peaks_valleys_map: OctavedNoise::new(derive_seed(seed, "peaks valleys"), 1.0 / 256.0),
+      erosion_map: OctavedNoise::new(derive_seed(seed, "erosion"), 1.0 / 2048.0),
       variance_map: ShiftedNoise::new(
-        VoronoiNoise::new(seed, 128),
-        OpenSimplexNoise::new(seed),
+        VoronoiNoise::new(derive_seed(seed, "variance"), 128),
+        OpenSimplexNoise::new(derive_seed(seed, "variance shift")),
         1.0,
         1.0,
       ),
 
-      density_map: OctavedNoise::new(seed, 1.0 / 64.0),
+      density_map: OctavedNoise::new(derive_seed(seed, "density"), 1.0 / 64.0),
 
-      sub_layer_map: OctavedNoise::new(seed, 1.0 / 20.0),
+      sub_layer_map: OctavedNoise::new(derive_seed(seed, "sub layer"), 1.0 / 20.0),
 
       global_chunk_placers: vec![
-        Box::new(chunk_placer::Ore {
-          ore:           block![coal_ore],
-          avg_per_chunk: 4.0,
-          size:          4..=12,
-          height:        0..=128,
-          width:         1.5,
-        }),
-        Box::new(chunk_placer::Ore {
-          ore:           block![iron_ore],
-          avg_per_chunk: 3.0,
-          size:          4..=8,
-          height:        0..=64,
-          width:         1.5,
-        }),
-        Box::new(chunk_placer::Ore {
-          ore:           block![gold_ore],
-          avg_per_chunk: 2.0,
-          size:          4..=8,
-          height:        0..=32,
-          width:         1.0,
-        }),
-        Box::new(chunk_placer::Ore {
-          ore:           block![redstone_ore],
-          avg_per_chunk: 1.0,
-          size:          4..=12,
-          height:        0..=32,
-          width:         1.0,
-        }),
-        Box::new(chunk_placer::Ore {
-          ore:           block![lapis_ore],
-          avg_per_chunk: 1.0,
-          size:          2..=6,
-          height:        0..=16,
-          width:         0.5,
-        }),
-        Box::new(chunk_placer::Ore {
-          ore:           block![diamond_ore],
-          avg_per_chunk: 1.0,
-          size:          2..=6,
-          height:        0..=16,
-          width:         0.5,
-        }),
+        ChunkPlacerBuilder::new(
+          Box::new(chunk_placer::Ore {
+            seed:          derive_seed(seed, "coal ore"),
+            ore:           block![coal_ore],
+            avg_per_chunk: 4.0,
+            size:          4..=12,
+            height:        0..=128,
+            width:         1.5,
+          }),
+          "coal ore",
+          derive_seed(seed, "global"),
+        ),
+        ChunkPlacerBuilder::new(
+          Box::new(chunk_placer::Ore {
+            seed:          derive_seed(seed, "iron ore"),
+            ore:           block![iron_ore],
+            avg_per_chunk: 3.0,
+            size:          4..=8,
+            height:        0..=64,
+            width:         1.5,
+          }),
+          "iron ore",
+          derive_seed(seed, "global"),
+        ),
+        ChunkPlacerBuilder::new(
+          Box::new(chunk_placer::Ore {
+            seed:          derive_seed(seed, "gold ore"),
+            ore:           block![gold_ore],
+            avg_per_chunk: 2.0,
+            size:          4..=8,
+            height:        0..=32,
+            width:         1.0,
+          }),
+          "gold ore",
+          derive_seed(seed, "global"),
+        ),
+        ChunkPlacerBuilder::new(
+          Box::new(chunk_placer::Ore {
+            seed:          derive_seed(seed, "redstone ore"),
+            ore:           block![redstone_ore],
+            avg_per_chunk: 1.0,
+            size:          4..=12,
+            height:        0..=32,
+            width:         1.0,
+          }),
+          "redstone ore",
+          derive_seed(seed, "global"),
+        ),
+        ChunkPlacerBuilder::new(
+          Box::new(chunk_placer::Ore {
+            seed:          derive_seed(seed, "lapis ore"),
+            ore:           block![lapis_ore],
+            avg_per_chunk: 1.0,
+            size:          2..=6,
+            height:        0..=16,
+            width:         0.5,
+          }),
+          "lapis ore",
+          derive_seed(seed, "global"),
+        ),
+        ChunkPlacerBuilder::new(
+          Box::new(chunk_placer::Ore {
+            seed:          derive_seed(seed, "diamond ore"),
+            ore:           block![diamond_ore],
+            avg_per_chunk: 1.0,
+            size:          2..=6,
+            height:        0..=16,
+            width:         0.5,
+          }),
+          "diamond ore",
+          derive_seed(seed, "global"),
+        ),
       ],
     }
   }
 
+  /// Returns the world seed this generator was created with.
+  pub fn seed(&self) -> u64 { self.seed }
+
+  /// Adds a carver, which will run after all the existing carvers.
+  pub fn add_carver(&mut self, carver: impl Carver + 'static) { self.cave.add(carver); }
+
+  /// Adds a structure type. See [`StructureType`].
+  pub fn add_structure(&mut self, name: &'static str, structure: impl StructureType + 'static) {
+    self.structure.add(name, structure);
+  }
+
+  /// Adds a loot table, which containers in structures can reference by name.
+  /// See [`LootTable`].
+  pub fn add_loot_table(&mut self, name: &'static str, table: LootTable) {
+    self.structure.add_loot_table(name, table);
+  }
+
+  /// Returns the names of all the structure types that can be located.
+  pub fn structure_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+    self.structure.names()
+  }
+
+  /// Finds the closest structure of the given type to `pos`, at most
+  /// `max_radius` blocks away on the X and Z axes.
+  pub fn locate_structure(&self, name: &str, pos: Pos, max_radius: i32) -> Option<Pos> {
+    self.structure.locate(self, name, pos, max_radius)
+  }
+
   pub fn sample_continentalness(&self, pos: Pos) -> f64 {
     (self.continentalness_map.generate(pos.x as f64, pos.z as f64) * 0.5 + 0.5).clamp(0.0, 1.0)
   }
@@ -264,16 +341,17 @@ impl Generator for WorldBiomes {
       return;
     }
 
-    self.generate_stone(ctx, chunk, chunk_pos);
+    let terrain = ChunkTerrain::new(self, chunk_pos);
 
-    self.cave.carve(self, chunk, chunk_pos);
+    self.generate_stone(ctx, &terrain, chunk, chunk_pos);
 
-    self.generate_top_layer(&ctx.blocks, chunk, chunk_pos);
-    self.generate_chunk_placers(&ctx.blocks, chunk, chunk_pos);
+    let aquifer = self.aquifer.chunk(self, &terrain, &ctx.blocks, chunk_pos);
+    self.cave.carve(self, &aquifer, chunk, chunk_pos);
 
-    if feature::VILLAGES {
-      self.structure.generate(&ctx.blocks, chunk, chunk_pos);
-    }
+    self.generate_top_layer(&ctx.blocks, &terrain, chunk, chunk_pos);
+    self.generate_chunk_placers(&ctx.blocks, &terrain, &aquifer, chunk, chunk_pos);
+
+    self.structure.generate(self, &ctx.blocks, chunk, chunk_pos);
   }
 
   fn decorate(&self, world: &mut PartialWorld, chunk_pos: ChunkPos) {
@@ -290,42 +368,51 @@ impl Generator for WorldBiomes {
     // TODO: Maybe make this 3D as well? Not sure if we want underground trees or
     // anything.
 
-    if feature::VILLAGES {
-      self.structure.decorate(world, chunk_pos);
-    }
+    self.structure.decorate(self, world, chunk_pos);
 
     let mut biome_names = [[""; 16]; 16];
-    // The length of this list is how many total biomes we support in a single
-    // chunk. If there are more biomes than this, the extra ones will not be
-    // decorated. This is an optimization to avoid allocating here.
-    let mut biome_index = 0;
-    let mut biome_set = [Option::<&BiomeBuilder>::None; 16];
+    // Chunks will almost never have more than 16 biomes, so this avoids
+    // allocating in the common case.
+    let mut biome_set = SmallVec::<[&BiomeBuilder; 16]>::new();
 
     for x in 0..16 {
       for z in 0..16 {
-        // Check at Y=255, to get all the surface biomes.
-        let pos = chunk_pos.min_block_pos() + Pos::new(x, 255, z);
-        let biome = self.choose_biome(pos);
+        let pos = chunk_pos.min_block_pos() + Pos::new(x, 0, z);
+        let biome = self.choose_blended_surface_biome(pos);
         biome_names[x as usize][z as usize] = biome.name;
 
         // `biome_set` acts like a set, so we need to check if this is a new biome or
         // not. Note that this means every biome name _must_ be unique.
-        if !biome_set[..biome_index].iter().any(|b| b.is_some_and(|b| b.name == biome.name))
-          && biome_index < biome_set.len()
-        {
-          biome_set[biome_index] = Some(biome);
-          biome_index += 1;
+        if !biome_set.iter().any(|b| b.name == biome.name) {
+          biome_set.push(biome);
         }
       }
     }
 
-    for biome in biome_set.into_iter().flatten() {
-      let mut rng = Rng::new(self.seed);
-      biome.decorate(&mut rng, chunk_pos, world, |pos| {
-        let rel_x = pos.x - chunk_pos.min_block_pos().x;
-        let rel_z = pos.z - chunk_pos.min_block_pos().z;
-        biome_names[rel_x as usize][rel_z as usize] == biome.name
-      });
+    // Each blend weight looks up 5 biomes, so only look them up once per column.
+    let blend_weights = [const { [const { Cell::new(None) }; 16] }; 16];
+
+    for biome in biome_set {
+      biome.decorate(
+        chunk_pos,
+        world,
+        |pos| {
+          let rel_x = pos.x - chunk_pos.min_block_pos().x;
+          let rel_z = pos.z - chunk_pos.min_block_pos().z;
+          biome_names[rel_x as usize][rel_z as usize] == biome.name
+        },
+        |pos| {
+          let rel_x = pos.x - chunk_pos.min_block_pos().x;
+          let rel_z = pos.z - chunk_pos.min_block_pos().z;
+          let cached: &Cell<Option<f64>> = &blend_weights[rel_x as usize][rel_z as usize];
+
+          cached.get().unwrap_or_else(|| {
+            let weight = self.blend_weight(pos);
+            cached.set(Some(weight));
+            weight
+          })
+        },
+      );
     }
 
     world.set(chunk_pos.min_block_pos() + Pos::new(0, 6, 0), block![dirt]);
@@ -333,7 +420,13 @@ impl Generator for WorldBiomes {
 }
 
 impl WorldBiomes {
-  fn generate_stone(&self, ctx: &Context, chunk: &mut Chunk, chunk_pos: ChunkPos) {
+  fn generate_stone(
+    &self,
+    ctx: &Context,
+    terrain: &ChunkTerrain,
+    chunk: &mut Chunk,
+    chunk_pos: ChunkPos,
+  ) {
     profile_function!();
 
     let stone = ctx.blocks.encode(block![stone]);
@@ -345,17 +438,19 @@ impl WorldBiomes {
 
         // let height = self.height_at(pos) as i32;
         // let biome = self.choose_biome(seed, pos);
-        let mut info = self.height_info(pos);
+        let info = terrain.height_info(self, pos);
 
         if info.max_height() < 64.0 {
+          let mut underground = [false; 64];
+          info.underground_column(0, &mut underground);
+
           for y in 0..=63 {
-            let pos = pos.with_y(y);
+            let rel_pos = pos.chunk_rel().with_y(y);
 
-            info.move_to(pos);
-            if info.underground() {
-              chunk.set(pos.chunk_rel(), stone);
+            if underground[y as usize] {
+              chunk.set(rel_pos, stone);
             } else {
-              chunk.set(pos.chunk_rel(), water);
+              chunk.set(rel_pos, water);
             }
           }
         } else {
@@ -363,12 +458,17 @@ impl WorldBiomes {
             chunk.set(pos.chunk_rel().with_y(y), stone);
           }
 
-          for y in info.min_height as i32..info.max_height as i32 {
-            let pos = pos.with_y(y);
+          let min_y = info.min_height as i32;
+          let max_y = (info.max_height as i32).min(256);
+
+          let mut underground = [false; 256];
+          if min_y < max_y {
+            info.underground_column(min_y, &mut underground[..(max_y - min_y) as usize]);
+          }
 
-            info.move_to(pos);
-            if info.underground() {
-              chunk.set(pos.chunk_rel(), stone);
+          for y in min_y..max_y {
+            if underground[(y - min_y) as usize] {
+              chunk.set(pos.chunk_rel().with_y(y), stone);
             }
           }
         }
@@ -388,6 +488,7 @@ impl WorldBiomes {
   fn generate_top_layer(
     &self,
     block_info: &BlockInfoSupplier,
+    terrain: &ChunkTerrain,
     chunk: &mut Chunk,
     chunk_pos: ChunkPos,
   ) {
@@ -403,7 +504,7 @@ impl WorldBiomes {
         let pos = chunk_pos.min_block_pos() + Pos::new(x, 0, z);
         let sub_layer_depth = self.sample_sub_layer_depth(pos);
 
-        let mut info = self.height_info(pos);
+        let mut info = terrain.height_info(self, pos);
 
         let mut depth = 0;
         let mut layer = 0;
@@ -412,7 +513,7 @@ impl WorldBiomes {
         // TODO: Fix.
         let mut underwater = false;
 
-        let biome = self.choose_surface_biome(pos);
+        let biome = self.choose_blended_surface_biome(pos);
 
         let min_height = (info.min_height as i32).min(40);
         for y in (min_height..=info.max_height as i32).rev() {
@@ -484,14 +585,13 @@ impl WorldBiomes {
   fn generate_chunk_placers(
     &self,
     info: &BlockInfoSupplier,
+    terrain: &ChunkTerrain,
+    aquifer: &ChunkAquifer,
     chunk: &mut Chunk,
     chunk_pos: ChunkPos,
   ) {
     profile_function!();
 
-    // The length of this list is how many total biomes we support in a single
-    // chunk. If there are more biomes than this, the extra ones will not be
-    // decorated. This is an optimization to avoid allocating here.
     let mut biome_set = TemporaryBiomeSet::new();
 
     let mut chunk = BiomeCachedChunk::new(info, chunk);
@@ -502,19 +602,31 @@ impl WorldBiomes {
       for x in 0..16 {
         for z in 0..16 {
           // This is kinda restrictive, but helps performance a _lot_. We also don't
-          // really want biomes to change on the Y axis, as that causes weirdness when
-          // building with grass and such. So we can limit ourselves to a single surface
-          // biome and a single cave biome per column.
-          let surface_biome =
-            self.choose_surface_biome(chunk_pos.min_block_pos() + Pos::new(x, 0, z));
-          let cave_biome = self.choose_cave_biome(chunk_pos.min_block_pos() + Pos::new(x, 0, z));
-
-          let info = self.height_info(chunk_pos.min_block_pos() + Pos::new(x, 0, z));
+          // really want surface biomes to change on the Y axis, as that causes weirdness
+          // when building with grass and such. So we can limit ourselves to a single
+          // surface biome per column, and a few cave biomes stacked in 16 block sections
+          // below it.
+          let pos = chunk_pos.min_block_pos() + Pos::new(x, 0, z);
+          let surface_biome = self.choose_blended_surface_biome(pos);
+          let info = terrain.height_info(self, pos);
+
+          let mut caves = CaveSpans::new(biome_set.add(self.choose_cave_biome(pos.with_y(8))));
+          for min_y in (16..info.min_height as i32).step_by(16) {
+            // Check before adding the biome to the set, so that chunks don't get
+            // biomes that are never placed.
+            if caves.is_full() {
+              debug!("dropping cave biomes above y={min_y} in column {pos:?}");
+              break;
+            }
+            caves.push(min_y, biome_set.add(self.choose_cave_biome(pos.with_y(min_y + 8))));
+          }
 
           let column = BiomeColumn {
-            surface:    biome_set.add(surface_biome),
-            cave:       biome_set.add(cave_biome),
+            surface: biome_set.add(surface_biome),
+            caves,
             min_height: info.min_height as i32,
+            blend_weight: self.blend_weight(pos) as f32,
+            fluid: aquifer.fluid_level(pos.chunk_rel()),
           };
 
           chunk.set_column(ChunkRelPos::new(x as u8, 0, z as u8), column);
@@ -524,32 +636,51 @@ impl WorldBiomes {
 
     {
       profile_scope!("biome chunk placers");
-      for (biome, id) in biome_set.set.into_iter().flatten() {
-        let mut rng = Rng::new(self.seed);
+      for (biome, id) in biome_set.set {
         chunk.set_active(id);
-        biome.generate(&mut rng, &mut chunk, chunk_pos);
+        biome.generate(&mut chunk, chunk_pos);
       }
     }
 
     {
       profile_scope!("global chunk placers");
       for placer in &self.global_chunk_placers {
-        placer.place(&mut chunk, &mut Rng::new(self.seed), chunk_pos);
+        placer.place(&mut chunk, chunk_pos);
       }
     }
   }
 
   pub fn height_info(&self, pos: Pos) -> HeightInfo {
-    let mut info =
-      HeightInfo { world: self, pos, max_height: 0.0, min_height: 0.0, underground: None };
+    let mut info = HeightInfo {
+      world: self,
+      terrain: None,
+      pos,
+      max_height: 0.0,
+      min_height: 0.0,
+      underground: None,
+    };
     info.change_xz();
     info
   }
+
+  /// Returns the Y level of the highest solid block of the terrain in the
+  /// given column. This ignores caves, water, and anything placed on top of the
+  /// terrain (like trees).
+  pub fn surface_height(&self, pos: Pos) -> i32 {
+    let info = self.height_info(pos);
+    let max_y = (info.max_height().ceil() as i32).clamp(1, 256) as usize;
+
+    let mut underground = [false; 256];
+    info.underground_column(0, &mut underground[..max_y]);
+
+    underground[..max_y].iter().rposition(|&u| u).unwrap_or(0) as i32
+  }
 }
 
 pub struct HeightInfo<'a> {
-  world: &'a WorldBiomes,
-  pos:   Pos,
+  world:   &'a WorldBiomes,
+  terrain: Option<&'a ChunkTerrain>,
+  pos:     Pos,
 
   // The "max height" here is the maximum Y level for a single block. We then
   // linearly interpolate between min_height and max_height, and compare the
@@ -562,11 +693,45 @@ pub struct HeightInfo<'a> {
   underground: Option<bool>,
 }
 
-impl HeightInfo<'_> {
+impl<'a> HeightInfo<'a> {
+  fn new_cached(
+    world: &'a WorldBiomes,
+    pos: Pos,
+    max_height: f64,
+    terrain: &'a ChunkTerrain,
+  ) -> Self {
+    HeightInfo {
+      world,
+      terrain: Some(terrain),
+      pos,
+      max_height,
+      min_height: 64.0 - max_height / 128.0,
+      underground: None,
+    }
+  }
+
   fn change_xz(&mut self) {
+    if let Some(terrain) = self.terrain {
+      // Moving out of the cached chunk needs to sample the height maps again.
+      if let Some(max_height) = terrain.max_height(self.pos) {
+        self.max_height = max_height;
+        self.min_height = 64.0 - self.max_height / 128.0;
+        return;
+      }
+
+      self.terrain = None;
+    }
+
     self.max_height = self.world.sample_height(self.pos);
     self.min_height = 64.0 - self.max_height / 128.0;
   }
+
+  fn sample_density(&self, pos: Pos) -> f64 {
+    match self.terrain.and_then(|t| t.density()) {
+      Some(lattice) => lattice.sample(pos),
+      None => self.world.density_map.generate_3d(pos.x as f64, pos.y as f64, pos.z as f64),
+    }
+  }
   fn change_y(&mut self) { self.underground = None; }
 
   pub fn move_to(&mut self, pos: Pos) {
@@ -581,54 +746,183 @@ impl HeightInfo<'_> {
     }
   }
 
-  pub fn max_height(&self) -> f64 { self.max_height }
-  pub fn min_height(&self) -> f64 { self.min_height }
-  pub fn underground(&mut self) -> bool {
-    *self.underground.get_or_insert_with(|| {
-      if self.min_height > self.max_height {
-        // Special case for oceans.
-        self.pos.y < self.max_height as i32
-      } else if self.pos.y < self.min_height as i32 {
+  /// Fills `out` with `underground` for each block in this column, starting
+  /// at `min_y`. This is the same as calling `move_to` and `underground` for
+  /// each block, but it samples the density map for the whole column at once.
+  pub fn underground_column(&self, min_y: i32, out: &mut [bool]) {
+    // Only sample the density map where it's needed.
+    let mut density = [0.0; 256];
+    let noise_min = (self.min_height as i32).max(min_y);
+    let noise_max = (self.max_height as i32).min(min_y + out.len() as i32);
+    if self.min_height <= self.max_height && noise_min < noise_max {
+      let density = &mut density[(noise_min - min_y) as usize..(noise_max - min_y) as usize];
+
+      match self.terrain.and_then(|t| t.density()) {
+        Some(lattice) => {
+          for (i, v) in density.iter_mut().enumerate() {
+            *v = lattice.sample(self.pos.with_y(noise_min + i as i32));
+          }
+        }
+        None => self.world.density_map.generate_column_3d(
+          self.pos.x as f64,
+          noise_min as f64,
+          self.pos.z as f64,
+          1.0,
+          density,
+        ),
+      }
+    }
+
+    for (i, underground) in out.iter_mut().enumerate() {
+      let y = min_y + i as i32;
+
+      *underground = if self.min_height > self.max_height {
+        y < self.max_height as i32
+      } else if y < self.min_height as i32 {
         true
-      } else if self.pos.y >= self.max_height as i32 {
+      } else if y >= self.max_height as i32 {
         false
       } else {
-        let noise = self.world.density_map.generate_3d(
-          self.pos.x as f64,
-          self.pos.y as f64,
-          self.pos.z as f64,
-        ) * 0.5
-          + 0.5;
-        let limit = (self.pos.y as f64 - self.min_height) / (self.max_height - self.min_height);
+        let noise = density[i] * 0.5 + 0.5;
+        let limit = (y as f64 - self.min_height) / (self.max_height - self.min_height);
 
         noise > limit
-      }
-    })
+      };
+    }
+  }
+
+  pub fn max_height(&self) -> f64 { self.max_height }
+  pub fn min_height(&self) -> f64 { self.min_height }
+  pub fn underground(&mut self) -> bool {
+    if let Some(underground) = self.underground {
+      return underground;
+    }
+
+    let underground = if self.min_height > self.max_height {
+      // Special case for oceans.
+      self.pos.y < self.max_height as i32
+    } else if self.pos.y < self.min_height as i32 {
+      true
+    } else if self.pos.y >= self.max_height as i32 {
+      false
+    } else {
+      let noise = self.sample_density(self.pos) * 0.5 + 0.5;
+      let limit = (self.pos.y as f64 - self.min_height) / (self.max_height - self.min_height);
+
+      noise > limit
+    };
+
+    self.underground = Some(underground);
+    underground
   }
 }
 
+/// The set of biomes in a single chunk, which assigns a `TemporaryBiome` to
+/// each biome.
+///
+/// Chunks will almost never have more than 16 biomes, so this avoids
+/// allocating in the common case.
 struct TemporaryBiomeSet<'a> {
-  set:   [Option<(&'a BiomeBuilder, TemporaryBiome)>; 16],
-  index: usize,
+  set: SmallVec<[(&'a BiomeBuilder, TemporaryBiome); 16]>,
 }
 
 impl<'a> TemporaryBiomeSet<'a> {
-  pub fn new() -> Self { TemporaryBiomeSet { set: [None; 16], index: 0 } }
+  pub fn new() -> Self { TemporaryBiomeSet { set: SmallVec::new() } }
 
   pub fn add(&mut self, biome: &'a BiomeBuilder) -> TemporaryBiome {
-    match self.set[..self.index].iter().find(|b| b.is_some_and(|(b, _)| b.name == biome.name)) {
-      Some(Some((_, id))) => *id,
-      Some(None) => unreachable!(),
+    match self.set.iter().find(|(b, _)| b.name == biome.name) {
+      Some((_, id)) => *id,
       None => {
-        if self.index < 15 {
-          let id = TemporaryBiome(self.index as u8);
-          self.set[self.index] = Some((biome, id));
-          self.index += 1;
-          id
-        } else {
-          TemporaryBiome(15)
+        // Each column has one surface biome and a few cave biomes, so a chunk can't
+        // have anywhere near `u16::MAX` biomes.
+        let id = TemporaryBiome(self.set.len() as u16);
+        self.set.push((biome, id));
+
+        if self.set.len() == 17 {
+          debug!("chunk has more than 16 biomes, this will allocate");
+        }
+
+        id
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn noise_maps_are_decorrelated() {
+    let ctx = Context::new_test(1234);
+    let biomes = WorldBiomes::new(&ctx.blocks, ctx.seed);
+
+    // Sample each map at the same frequency, so that maps with the same seed
+    // would line up exactly.
+    let maps: [(&str, &dyn NoiseGenerator<Output = f64>); 6] = [
+      ("temperature", &biomes.temperature_map.with_freq(1.0 / 64.0)),
+      ("humidity", &biomes.humidity_map.with_freq(1.0 / 64.0)),
+      ("continentalness", &biomes.continentalness_map.with_freq(1.0 / 64.0)),
+      ("peaks valleys", &biomes.peaks_valleys_map.with_freq(1.0 / 64.0)),
+      ("erosion", &biomes.erosion_map.with_freq(1.0 / 64.0)),
+      ("sub layer", &biomes.sub_layer_map.with_freq(1.0 / 64.0)),
+    ];
+
+    let samples = maps.map(|(name, map)| {
+      let mut values = vec![];
+      for x in 0..64 {
+        for z in 0..64 {
+          values.push(map.generate(x as f64 * 16.0, z as f64 * 16.0));
         }
       }
+      (name, values)
+    });
+
+    fn correlation(a: &[f64], b: &[f64]) -> f64 {
+      let mean_a = a.iter().sum::<f64>() / a.len() as f64;
+      let mean_b = b.iter().sum::<f64>() / b.len() as f64;
+
+      let mut cov = 0.0;
+      let mut var_a = 0.0;
+      let mut var_b = 0.0;
+      for (a, b) in a.iter().zip(b) {
+        cov += (a - mean_a) * (b - mean_b);
+        var_a += (a - mean_a).powi(2);
+        var_b += (b - mean_b).powi(2);
+      }
+
+      cov / (var_a * var_b).sqrt()
+    }
+
+    for (i, (name_a, a)) in samples.iter().enumerate() {
+      for (name_b, b) in &samples[i + 1..] {
+        let c = correlation(a, b);
+        assert!(c.abs() < 0.2, "{name_a} and {name_b} are correlated ({c})");
+      }
     }
   }
+
+  #[test]
+  fn find_biome() {
+    let ctx = Context::new_test(1234);
+    let biomes = WorldBiomes::new(&ctx.blocks, ctx.seed);
+
+    for i in 0..8 {
+      let origin = Pos::new(i * 300, 0, i * -200);
+      let target = origin + Pos::new(400, 0, 300);
+
+      let name = biomes.choose_blended_surface_biome(target).name;
+      let found = biomes.find_biome(name, origin, 1024).unwrap();
+      assert_eq!(biomes.choose_blended_surface_biome(found).name, name);
+      assert!((found.x - origin.x).pow(2) + (found.z - origin.z).pow(2) <= 1024 * 1024);
+
+      let geographic = biomes.geographic_type(target);
+      let climate = biomes.climate_type(target);
+      let found = biomes.find_biome_type(geographic, climate, origin, 1024).unwrap();
+      assert_eq!(biomes.geographic_type(found), geographic);
+      assert_eq!(biomes.climate_type(found), climate);
+    }
+
+    assert_eq!(biomes.find_biome("not_a_biome", Pos::new(0, 0, 0), 256), None);
+  }
 }
@@ -2,13 +2,16 @@ use std::{cell::RefCell, num::NonZero};
 
 use lru::LruCache;
 use rgen_base::Pos;
-use rgen_placer::noise::NoiseGenerator;
+use rgen_placer::{
+  Random, Rng, derive_pos_seed, derive_seed,
+  noise::{NoiseGenerator, NoiseGenerator3D},
+};
 
 use crate::{
   WorldBiomes,
   builder::BiomeBuilder,
   feature,
-  table::{CLIMATE_TABLE, ClimateType, GeographicType},
+  table::{BiomeComposition, CLIMATE_TABLE, CaveClimate, CaveDepth, ClimateType, GeographicType},
 };
 
 #[derive(Debug)]
@@ -30,6 +33,15 @@ pub enum PeaksValleysCategory {
   Peak,
 }
 
+/// The maximum distance, in blocks, that biomes will be blended into each
+/// other.
+const BLEND_RADIUS: i32 = 4;
+
+/// The distance between columns sampled when searching for a biome.
+const SEARCH_STEP: i32 = 32;
+/// The distance between columns sampled when refining a search result.
+const REFINE_STEP: i32 = 4;
+
 // This is a key to lookup any biome quickly. This key is generated from all the
 // noise maps and composition tables.
 #[derive(Clone, Copy)]
@@ -49,12 +61,40 @@ impl WorldBiomes {
     }
   }
 
-  pub fn choose_cave_biome(&self, _pos: Pos) -> &BiomeBuilder {
-    // FIXME: This needs rewriting.
-    /*
-    let biomes = &self.old_table[(temperature * self.old_table.len() as f64) as usize]   [(humidity * self.old_table[0].len() as f64) as usize];
-    */
-    &self.composition_lookup.blank[0]
+  pub fn choose_cave_biome(&self, pos: Pos) -> &BiomeBuilder {
+    if crate::feature::BIOME_OVERRIDE {
+      return &self.composition_lookup.blank[0];
+    }
+
+    let biomes = self.composition_lookup.choose_cave(self.cave_depth(pos), self.cave_climate(pos));
+    choose_by_rarity(biomes, self.variance(pos))
+  }
+
+  pub fn cave_depth(&self, pos: Pos) -> CaveDepth {
+    match pos.y {
+      40.. => CaveDepth::Shallow,
+      16.. => CaveDepth::Deep,
+      _ => CaveDepth::Core,
+    }
+  }
+
+  pub fn cave_climate(&self, pos: Pos) -> CaveClimate {
+    let (x, y, z) = (pos.x as f64, pos.y as f64, pos.z as f64);
+
+    // Caves follow the climate of the surface, but get hotter the deeper they are.
+    let heat = (1.0 - y / 64.0).clamp(0.0, 1.0) * 0.2;
+    let temperature = self.temperature(pos) * 0.5
+      + (self.cave_temperature_map.generate_3d(x, y, z) * 0.5 + 0.5) * 0.5
+      + heat;
+    let humidity =
+      self.humidity(pos) * 0.5 + (self.cave_humidity_map.generate_3d(x, y, z) * 0.5 + 0.5) * 0.5;
+
+    match (temperature, humidity) {
+      (t, _) if t < 0.3 => CaveClimate::Frozen,
+      (t, _) if t > 0.7 => CaveClimate::Hot,
+      (_, h) if h > 0.6 => CaveClimate::Lush,
+      _ => CaveClimate::Temperate,
+    }
   }
 
   pub fn geographic_type(&self, pos: Pos) -> GeographicType {
@@ -132,23 +172,138 @@ impl WorldBiomes {
     self.choose_surface_biome_from_key(key)
   }
 
+  /// Chooses the surface biome at a randomly jittered position near `pos`. This
+  /// dithers the edges of biomes together, so this should be used for anything
+  /// placing blocks, and `choose_surface_biome` should be used for anything
+  /// that wants smooth biome borders (like the biome map sent to the client).
+  pub fn choose_blended_surface_biome(&self, pos: Pos) -> &BiomeBuilder {
+    self.choose_surface_biome(self.blend_jitter(pos))
+  }
+
+  /// Returns how far the given column is into its blended biome. This is 1.0
+  /// deep inside a biome, and drops towards 0.0 near the edges of a biome.
+  pub fn blend_weight(&self, pos: Pos) -> f64 {
+    if !feature::BIOME_BLENDING {
+      return 1.0;
+    }
+
+    let biome = self.choose_blended_surface_biome(pos).name;
+
+    let samples = [
+      Pos::new(BLEND_RADIUS, 0, 0),
+      Pos::new(-BLEND_RADIUS, 0, 0),
+      Pos::new(0, 0, BLEND_RADIUS),
+      Pos::new(0, 0, -BLEND_RADIUS),
+    ];
+
+    let matching = samples
+      .iter()
+      .filter(|&&offset| self.choose_surface_biome(pos + offset).name == biome)
+      .count();
+
+    matching as f64 / samples.len() as f64
+  }
+
+  fn blend_jitter(&self, pos: Pos) -> Pos {
+    if !feature::BIOME_BLENDING {
+      return pos;
+    }
+
+    let mut rng = Rng::new(derive_pos_seed(derive_seed(self.seed, "biome blend"), pos.with_y(0)));
+    pos
+      + Pos::new(
+        rng.range(-BLEND_RADIUS..=BLEND_RADIUS),
+        0,
+        rng.range(-BLEND_RADIUS..=BLEND_RADIUS),
+      )
+  }
+
+  /// Finds the closest column to `origin` with the given surface biome, at
+  /// most `radius` blocks away. This matches the blended biome, which is the
+  /// one whose blocks are placed at that column. The returned position is on
+  /// top of the terrain.
+  pub fn find_biome(&self, name: &str, origin: Pos, radius: i32) -> Option<Pos> {
+    self.find_column(origin, radius, |pos| self.choose_blended_surface_biome(pos).name == name)
+  }
+
+  /// Finds the closest column to `origin` with the given geographic and
+  /// climate type, at most `radius` blocks away.
+  pub fn find_biome_type(
+    &self,
+    geographic: GeographicType,
+    climate: ClimateType,
+    origin: Pos,
+    radius: i32,
+  ) -> Option<Pos> {
+    self.find_column(origin, radius, |pos| {
+      let key = self.choose_surface_biome_cached(pos);
+      key.geographic == geographic && key.climate == climate
+    })
+  }
+
+  /// Searches outward from `origin` in square rings of `SEARCH_STEP` blocks.
+  /// The closest match in each ring is refined by searching around it in steps
+  /// of `REFINE_STEP`. Because the corners of a ring are further away than its
+  /// edges, rings are searched until they are all further away than the best
+  /// match.
+  fn find_column(&self, origin: Pos, radius: i32, matches: impl Fn(Pos) -> bool) -> Option<Pos> {
+    profile_function!();
+
+    let origin = origin.with_y(0);
+    let distance = |pos: Pos| {
+      let offset = pos - origin;
+      i64::from(offset.x).pow(2) + i64::from(offset.z).pow(2)
+    };
+    let in_range = |pos: Pos| distance(pos) <= i64::from(radius).pow(2);
+
+    let mut best: Option<Pos> = None;
+    for ring in 0..=radius / SEARCH_STEP {
+      // Every position in this ring is at least `ring * SEARCH_STEP` away.
+      if best.is_some_and(|best| i64::from(ring * SEARCH_STEP).pow(2) >= distance(best)) {
+        break;
+      }
+
+      let mut closest = None;
+      for x in -ring..=ring {
+        for z in -ring..=ring {
+          if x.abs() != ring && z.abs() != ring {
+            continue;
+          }
+
+          let pos = origin + Pos::new(x, 0, z) * SEARCH_STEP;
+          if in_range(pos) && matches(pos) && closest.is_none_or(|c| distance(pos) < distance(c)) {
+            closest = Some(pos);
+          }
+        }
+      }
+
+      let Some(coarse) = closest else { continue };
+
+      let mut refined = coarse;
+      for x in -SEARCH_STEP / REFINE_STEP..=SEARCH_STEP / REFINE_STEP {
+        for z in -SEARCH_STEP / REFINE_STEP..=SEARCH_STEP / REFINE_STEP {
+          let pos = coarse + Pos::new(x, 0, z) * REFINE_STEP;
+          if distance(pos) < distance(refined) && in_range(pos) && matches(pos) {
+            refined = pos;
+          }
+        }
+      }
+
+      if best.is_none_or(|best| distance(refined) < distance(best)) {
+        best = Some(refined);
+      }
+    }
+
+    best.map(|best| best.with_y(self.surface_height(best)))
+  }
+
   fn choose_surface_biome_from_key(&self, key: BiomeKey) -> &BiomeBuilder {
     if crate::feature::BIOME_OVERRIDE {
       return &self.composition_lookup.blank[0];
     }
 
     let biomes = self.composition_lookup.choose(key.geographic, key.climate);
-
-    let total = biomes.iter().map(|b| b.rarity).sum::<u32>();
-    let mut variance = key.variance % total;
-    for biome in biomes {
-      variance = match variance.checked_sub(biome.rarity) {
-        Some(v) => v,
-        None => return biome,
-      };
-    }
-
-    &biomes[0]
+    choose_by_rarity(biomes, key.variance)
   }
 
   fn choose_surface_biome_cached(&self, pos: Pos) -> BiomeKey {
@@ -241,3 +396,38 @@ impl WorldBiomes {
     self.variance_map.generate(pos.x as f64 / 128.0, pos.z as f64 / 128.0)
   }
 }
+
+/// Picks a biome out of the composition, where each biome is picked
+/// proportionally to its rarity.
+fn choose_by_rarity(biomes: &BiomeComposition, variance: u32) -> &BiomeBuilder {
+  let total = biomes.iter().map(|b| b.rarity).sum::<u32>();
+  let mut variance = variance % total;
+  for biome in biomes {
+    variance = match variance.checked_sub(biome.rarity) {
+      Some(v) => v,
+      None => return biome,
+    };
+  }
+
+  &biomes[0]
+}
+
+#[cfg(test)]
+mod tests {
+  use rgen_world::Context;
+
+  use super::*;
+
+  #[test]
+  fn find_column_keeps_searching() {
+    let ctx = Context::new_test(1234);
+    let biomes = WorldBiomes::new(&ctx.blocks, ctx.seed);
+
+    // The corner of the third ring is further away than the edge of the fourth.
+    let corner = Pos::new(3 * SEARCH_STEP, 0, 3 * SEARCH_STEP);
+    let edge = Pos::new(0, 0, 4 * SEARCH_STEP);
+    let found = biomes.find_column(Pos::new(0, 0, 0), 1024, |pos| pos == corner || pos == edge);
+
+    assert_eq!(found.map(|pos| pos.with_y(0)), Some(edge));
+  }
+}
@@ -0,0 +1,36 @@
+use std::{
+  num::NonZero,
+  sync::{Arc, Mutex},
+};
+
+use lru::LruCache;
+use rgen_base::Pos;
+
+/// Caches the layout of each structure by its origin. Structures span many
+/// chunks, and each of those chunks needs the same layout in both passes, so
+/// this avoids laying out a structure over and over.
+pub struct LayoutCache<T> {
+  layouts: Mutex<LruCache<(i32, i32), Arc<T>>>,
+}
+
+impl<T> LayoutCache<T> {
+  pub fn new() -> Self {
+    LayoutCache { layouts: Mutex::new(LruCache::new(NonZero::new(64).unwrap())) }
+  }
+
+  /// Returns the layout of the structure at `origin`, calling `layout` if it
+  /// isn't cached.
+  pub fn get(&self, origin: Pos, layout: impl FnOnce() -> T) -> Arc<T> {
+    let key = (origin.x, origin.z);
+    if let Some(cached) = self.layouts.lock().unwrap().get(&key) {
+      return cached.clone();
+    }
+
+    // Layouts only depend on the origin, so if two threads both miss, they'll
+    // build the same thing. That's better than blocking every other thread on
+    // the lock while laying out a structure.
+    let layout = Arc::new(layout());
+    self.layouts.lock().unwrap().put(key, layout.clone());
+    layout
+  }
+}
@@ -0,0 +1,35 @@
+orientation horizontal
+
+B: minecraft:stonebrick
+M: minecraft:stonebrick[variant=mossy_stonebrick]
+C: minecraft:cobblestone
+O: minecraft:mossy_cobblestone
+_: minecraft:air
+H: connector hall
+
+layer end
+B B B
+B _ B
+B H B
+C C C
+==
+
+layer middle
+B B B
+B _ B
+B _ B
+C C C
+==
+
+repeat middle
+
+layer mossy
+B M B
+M _ B
+B _ M
+C O C
+==
+
+repeat middle
+repeat middle
+repeat end
@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use rgen_base::{BlockFilter, ChunkPos, Pos, block};
+use rgen_placer::{Random, Rng};
+use rgen_world::PartialWorld;
+
+use super::{
+  StructureType,
+  cache::LayoutCache,
+  jigsaw::{Jigsaw, PlacedPiece},
+  math::Rectangle,
+  place_block_entity,
+};
+use crate::WorldBiomes;
+
+/// Underground rooms, joined together by corridors.
+pub struct DungeonGenerator {
+  jigsaw:      Jigsaw,
+  room:        usize,
+  layouts:     LayoutCache<Vec<PlacedPiece>>,
+  /// The blocks that `IfReplaceable` and `Foundation` cells can replace.
+  replaceable: BlockFilter,
+}
+
+impl DungeonGenerator {
+  pub fn new() -> Self {
+    let mut jigsaw = Jigsaw::new(4);
+    let room = jigsaw.add(rgen_llama::parse(include_str!("room.ll")).unwrap(), 1);
+    jigsaw.add(rgen_llama::parse(include_str!("corridor.ll")).unwrap(), 3);
+
+    DungeonGenerator {
+      jigsaw,
+      room,
+      layouts: LayoutCache::new(),
+      replaceable: [block![air], block![water], block![lava]].into(),
+    }
+  }
+
+  fn pieces(&self, seed: u64, origin: Pos) -> Arc<Vec<PlacedPiece>> {
+    self.layouts.get(origin, || {
+      let mut rng = Rng::new(seed);
+      let y = dungeon_height(&mut rng);
+
+      self.jigsaw.assemble(&mut rng, self.room, origin.with_y(y))
+    })
+  }
+}
+
+/// The Y level of the first room.
+fn dungeon_height(rng: &mut Rng) -> i32 { rng.range(12..=32) }
+
+impl StructureType for DungeonGenerator {
+  fn spacing(&self) -> f64 { 192.0 }
+
+  fn radius(&self) -> i32 { 64 }
+
+  fn locate(&self, seed: u64, origin: Pos, _: &WorldBiomes) -> Option<Pos> {
+    Some(origin.with_y(dungeon_height(&mut Rng::new(seed))))
+  }
+
+  fn decorate(
+    &self,
+    seed: u64,
+    origin: Pos,
+    world_biomes: &WorldBiomes,
+    world: &mut PartialWorld,
+    chunk_pos: ChunkPos,
+  ) {
+    let min = chunk_pos.min_block_pos();
+    let chunk = Rectangle { min, max: min + Pos::new(15, 0, 15) };
+
+    // Each chunk places the part of the dungeon inside it, so the pieces can be
+    // bigger than a chunk.
+    for piece in self.pieces(seed, origin).iter() {
+      if !self.jigsaw.bounding_box(piece).intersects(&chunk) {
+        continue;
+      }
+
+      let structure = self.jigsaw.structure(piece);
+      for rel_pos in structure.blocks() {
+        let pos = piece.pos + rel_pos;
+        if pos.in_chunk(chunk_pos) {
+          world.place_structure_block(
+            pos,
+            structure.get(rel_pos),
+            structure.placement(rel_pos),
+            &self.replaceable,
+          );
+        }
+      }
+      for (rel_pos, block_entity) in structure.block_entities() {
+        let pos = piece.pos + *rel_pos;
+        if pos.in_chunk(chunk_pos) {
+          place_block_entity(world_biomes, world, pos, block_entity);
+        }
+      }
+      for (rel_pos, entity) in structure.entities() {
+        let pos = piece.pos + *rel_pos;
+        if pos.in_chunk(chunk_pos) {
+          world.add_entity(pos, entity.clone());
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,48 @@
+orientation horizontal
+
+B: minecraft:stonebrick
+M: minecraft:stonebrick[variant=mossy_stonebrick]
+C: minecraft:cobblestone
+O: minecraft:mossy_cobblestone
+_: minecraft:air
+H: connector hall
+L: minecraft:chest[facing=south] loot rgen:dungeon
+
+layer wall
+B B B B B B B B B
+B M B B B B M B B
+B B B B _ B B B B
+B B M B H B B M B
+C C C C C C C C C
+==
+
+layer chest
+B B B B B B B B B
+B _ _ _ _ _ _ _ B
+M _ _ _ _ _ _ _ B
+B L _ _ _ _ _ _ M
+C C O C C C O C C
+==
+
+layer middle
+B B B B B B B B B
+B _ _ _ _ _ _ _ B
+M _ _ _ _ _ _ _ B
+B _ _ _ _ _ _ _ M
+C C O C C C O C C
+==
+
+repeat middle
+
+layer side
+B B B B B B B B B
+B _ _ _ _ _ _ _ B
+_ _ _ _ _ _ _ _ _
+H _ _ _ _ _ _ _ H
+C O C C O C C O C
+==
+
+repeat middle
+repeat middle
+repeat middle
+repeat wall
@@ -0,0 +1,288 @@
+//! Builds structures out of pieces, by attaching pieces together at their
+//! connectors.
+//!
+//! Each piece is a llama [`Structure`] with some connectors on its sides.
+//! Starting from a single piece, each open connector is matched with a
+//! connector of the same tag on another piece, facing the other way. Pieces
+//! that would overlap an existing piece are skipped, and the structure stops
+//! growing once it reaches the depth limit.
+//!
+//! Only dungeons are built with this. Villages lay out their own roads and
+//! houses, as each house is fitted into the terrain along a road, instead of
+//! being joined to other pieces.
+
+use std::collections::VecDeque;
+
+use rgen_base::{Direction, Pos};
+use rgen_llama::Structure;
+use rgen_placer::{Random, Rng};
+
+use super::math::Rectangle;
+
+/// The rotations of a piece, in the order they're stored in.
+const ROTATIONS: [Direction; 4] =
+  [Direction::North, Direction::East, Direction::South, Direction::West];
+
+pub struct Jigsaw {
+  pieces:    Vec<Piece>,
+  max_depth: u32,
+}
+
+struct Piece {
+  /// The piece rotated to face each of the [`ROTATIONS`].
+  rotations: [Structure; 4],
+  weight:    u32,
+}
+
+/// A single piece of an assembled structure.
+#[derive(Debug, Clone)]
+pub struct PlacedPiece {
+  pub piece:  usize,
+  /// The rotation of the piece. `North` means it is unrotated.
+  pub facing: Direction,
+  /// The minimum corner of the piece.
+  pub pos:    Pos,
+  /// The number of pieces between this one and the start piece.
+  pub depth:  u32,
+}
+
+/// A connector on a rotated piece, that can be attached to an open connector.
+#[derive(Clone, Copy)]
+struct Candidate {
+  piece:     usize,
+  facing:    Direction,
+  connector: usize,
+  weight:    u32,
+}
+
+/// A connector that hasn't been attached to anything yet.
+struct OpenConnector<'a> {
+  pos:    Pos,
+  facing: Direction,
+  tag:    &'a str,
+  depth:  u32,
+}
+
+impl Jigsaw {
+  /// Creates an empty jigsaw. Pieces will be placed at most `max_depth`
+  /// connectors away from the start piece.
+  pub fn new(max_depth: u32) -> Self { Jigsaw { pieces: vec![], max_depth } }
+
+  /// Adds a piece, and returns its index. Pieces with a higher weight are
+  /// picked more often.
+  pub fn add(&mut self, structure: Structure, weight: u32) -> usize {
+    let rotations = [0, 1, 2, 3].map(|delta| {
+      let mut rotated = structure.clone();
+      rotated.rotate(delta);
+      rotated
+    });
+
+    self.pieces.push(Piece { rotations, weight });
+    self.pieces.len() - 1
+  }
+
+  /// Returns the rotated structure of a placed piece.
+  pub fn structure(&self, piece: &PlacedPiece) -> &Structure {
+    let rotation = ROTATIONS.iter().position(|&dir| dir == piece.facing).unwrap();
+    &self.pieces[piece.piece].rotations[rotation]
+  }
+
+  /// Returns the area a placed piece covers.
+  pub fn bounding_box(&self, piece: &PlacedPiece) -> Rectangle {
+    let structure = self.structure(piece);
+    let size = Pos::new(
+      structure.width() as i32 - 1,
+      structure.height() as i32 - 1,
+      structure.depth() as i32 - 1,
+    );
+
+    Rectangle { min: piece.pos, max: piece.pos + size }
+  }
+
+  /// Assembles a structure, starting with the piece `start` centered on
+  /// `origin`.
+  pub fn assemble(&self, rng: &mut Rng, start: usize, origin: Pos) -> Vec<PlacedPiece> {
+    let facing = *rng.choose(&ROTATIONS);
+    let mut placed = PlacedPiece { piece: start, facing, pos: origin, depth: 0 };
+    let structure = self.structure(&placed);
+    placed.pos = origin - Pos::new(structure.width() as i32 / 2, 0, structure.depth() as i32 / 2);
+
+    let mut pieces = vec![];
+    let mut bounds = vec![];
+    let mut open = VecDeque::new();
+    self.push_piece(placed, None, &mut pieces, &mut bounds, &mut open);
+
+    while let Some(connector) = open.pop_front() {
+      if connector.depth >= self.max_depth {
+        continue;
+      }
+
+      // Every piece, rotation, and connector that could attach here.
+      let mut candidates = vec![];
+      for (index, piece) in self.pieces.iter().enumerate() {
+        for (rotation, structure) in piece.rotations.iter().enumerate() {
+          for (i, other) in structure.connectors().iter().enumerate() {
+            if other.tag == connector.tag && other.facing == connector.facing.opposite() {
+              candidates.push(Candidate {
+                piece:     index,
+                facing:    ROTATIONS[rotation],
+                connector: i,
+                weight:    piece.weight,
+              });
+            }
+          }
+        }
+      }
+
+      while let Some(candidate) = choose_weighted(rng, &mut candidates) {
+        let mut placed = PlacedPiece {
+          piece:  candidate.piece,
+          facing: candidate.facing,
+          pos:    Pos::new(0, 0, 0),
+          depth:  connector.depth + 1,
+        };
+        let other = &self.structure(&placed).connectors()[candidate.connector];
+
+        // The two connectors end up next to each other.
+        placed.pos = connector.pos + connector.facing.dir() - other.pos;
+
+        let rect = self.bounding_box(&placed);
+        if bounds.iter().any(|b: &Rectangle| b.intersects(&rect)) {
+          continue;
+        }
+
+        self.push_piece(placed, Some(candidate.connector), &mut pieces, &mut bounds, &mut open);
+        break;
+      }
+    }
+
+    pieces
+  }
+
+  /// Adds a piece to the structure, and opens up all of its connectors, other
+  /// than the one it was attached with.
+  fn push_piece<'a>(
+    &'a self,
+    placed: PlacedPiece,
+    attached: Option<usize>,
+    pieces: &mut Vec<PlacedPiece>,
+    bounds: &mut Vec<Rectangle>,
+    open: &mut VecDeque<OpenConnector<'a>>,
+  ) {
+    for (i, connector) in self.structure(&placed).connectors().iter().enumerate() {
+      if Some(i) == attached {
+        continue;
+      }
+
+      open.push_back(OpenConnector {
+        pos:    placed.pos + connector.pos,
+        facing: connector.facing,
+        tag:    &connector.tag,
+        depth:  placed.depth,
+      });
+    }
+
+    bounds.push(self.bounding_box(&placed));
+    pieces.push(placed);
+  }
+}
+
+/// Removes and returns a random candidate, where candidates with a higher
+/// weight are more likely to be picked.
+fn choose_weighted(rng: &mut Rng, candidates: &mut Vec<Candidate>) -> Option<Candidate> {
+  let total = candidates.iter().map(|c| c.weight).sum::<u32>();
+  if total == 0 {
+    return None;
+  }
+
+  let mut value = rng.range(0..total as i32) as u32;
+  for i in 0..candidates.len() {
+    if value < candidates[i].weight {
+      return Some(candidates.swap_remove(i));
+    }
+    value -= candidates[i].weight;
+  }
+
+  unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const ROOM: &str = "orientation horizontal
+
+S: minecraft:stone
+H: connector hall
+
+layer wall
+S S H S S
+==
+
+layer middle
+S       S
+==
+
+layer side
+H       H
+==
+
+repeat middle
+
+layer back
+S S H S S
+==
+";
+
+  const HALL: &str = "orientation horizontal
+
+S: minecraft:stone
+H: connector hall
+
+layer front
+S H S
+==
+
+layer middle
+S   S
+==
+
+repeat middle
+
+layer back
+S H S
+==
+";
+
+  #[test]
+  fn pieces_dont_overlap() {
+    let mut jigsaw = Jigsaw::new(6);
+    let room = jigsaw.add(rgen_llama::parse(ROOM).unwrap(), 1);
+    jigsaw.add(rgen_llama::parse(HALL).unwrap(), 3);
+
+    for seed in 0..32 {
+      let mut rng = Rng::new(seed);
+      let pieces = jigsaw.assemble(&mut rng, room, Pos::new(100, 20, -40));
+
+      assert!(pieces.len() > 1);
+      assert!(pieces.iter().all(|p| p.depth <= 6));
+
+      for (i, a) in pieces.iter().enumerate() {
+        for b in &pieces[i + 1..] {
+          assert!(!jigsaw.bounding_box(a).intersects(&jigsaw.bounding_box(b)));
+        }
+      }
+
+      // Every piece other than the first is attached to another piece.
+      for piece in &pieces[1..] {
+        let connected = jigsaw.structure(piece).connectors().iter().any(|c| {
+          let next = piece.pos + c.pos + c.facing.dir();
+          pieces.iter().any(|other| {
+            jigsaw.structure(other).connectors().iter().any(|o| other.pos + o.pos == next)
+          })
+        });
+        assert!(connected, "{piece:?} is not connected");
+      }
+    }
+  }
+}
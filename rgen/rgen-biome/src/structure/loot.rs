@@ -0,0 +1,208 @@
+//! Loot tables, which fill in the containers placed by structures.
+//!
+//! Containers reference a loot table by name. If the name is one of the tables
+//! registered here, the container is filled in when it is generated. Any other
+//! name is left for Minecraft to fill in when the container is first opened, so
+//! vanilla tables like `minecraft:chests/simple_dungeon` work as well.
+
+use std::{collections::HashMap, ops::RangeInclusive};
+
+use rgen_base::{BlockEntity, Nbt, NbtCompound, Pos};
+use rgen_placer::{Random, Rng, derive_pos_seed};
+
+/// The number of slots in a chest.
+const CONTAINER_SLOTS: i32 = 27;
+
+/// A list of items to randomly pick from.
+pub struct LootTable {
+  /// The number of times an entry is picked.
+  rolls:   RangeInclusive<i32>,
+  entries: Vec<LootEntry>,
+}
+
+struct LootEntry {
+  weight: u32,
+  item:   &'static str,
+  meta:   i16,
+  count:  RangeInclusive<i32>,
+}
+
+/// A stack of items, generated from a loot table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemStack {
+  /// The item id, like `minecraft:bread`.
+  pub item:  &'static str,
+  pub meta:  i16,
+  pub count: i32,
+}
+
+/// All the loot tables that are filled in at generation time.
+pub struct LootTables {
+  seed:   u64,
+  tables: HashMap<&'static str, LootTable>,
+}
+
+impl LootTable {
+  /// Creates an empty table, which picks an entry `rolls` times.
+  pub fn new(rolls: RangeInclusive<i32>) -> Self { LootTable { rolls, entries: vec![] } }
+
+  /// Adds an item. Items with a higher weight are picked more often.
+  pub fn entry(self, weight: u32, item: &'static str, count: RangeInclusive<i32>) -> Self {
+    self.entry_with_meta(weight, item, 0, count)
+  }
+
+  /// Adds an item with the given metadata, like `minecraft:dye` with a
+  /// metadata of 4 for lapis lazuli.
+  pub fn entry_with_meta(
+    mut self,
+    weight: u32,
+    item: &'static str,
+    meta: i16,
+    count: RangeInclusive<i32>,
+  ) -> Self {
+    self.entries.push(LootEntry { weight, item, meta, count });
+    self
+  }
+
+  /// Rolls the table, and returns the items picked.
+  pub fn generate(&self, rng: &mut Rng) -> Vec<ItemStack> {
+    let total = self.entries.iter().map(|e| e.weight).sum::<u32>();
+    if total == 0 {
+      return vec![];
+    }
+
+    let rolls = rng.range(self.rolls.clone());
+    (0..rolls)
+      .map(|_| {
+        let mut value = rng.range(0..total as i32) as u32;
+        let entry = self
+          .entries
+          .iter()
+          .find(|e| {
+            if value < e.weight {
+              return true;
+            }
+            value -= e.weight;
+            false
+          })
+          .unwrap();
+
+        ItemStack { item: entry.item, meta: entry.meta, count: rng.range(entry.count.clone()) }
+      })
+      .filter(|stack| stack.count > 0)
+      .collect()
+  }
+
+  /// Rolls the table, and puts each item in a random slot of the container.
+  /// Items that don't fit are dropped.
+  pub fn fill(&self, rng: &mut Rng, data: &mut NbtCompound) {
+    let mut slots = (0..CONTAINER_SLOTS).collect::<Vec<_>>();
+    rng.shuffle(&mut slots);
+
+    let items = self
+      .generate(rng)
+      .into_iter()
+      .zip(slots)
+      .map(|(stack, slot)| {
+        Nbt::Compound(
+          NbtCompound::new()
+            .with("Slot", slot as i8)
+            .with("id", stack.item)
+            .with("Count", stack.count.min(i8::MAX.into()) as i8)
+            .with("Damage", stack.meta),
+        )
+      })
+      .collect::<Vec<_>>();
+
+    data.insert("Items", items);
+  }
+}
+
+impl LootTables {
+  pub fn new(seed: u64) -> Self {
+    let mut tables = LootTables { seed, tables: HashMap::new() };
+
+    tables.add(
+      "rgen:dungeon",
+      LootTable::new(3..=6)
+        .entry(20, "minecraft:bread", 1..=3)
+        .entry(20, "minecraft:rotten_flesh", 2..=6)
+        .entry(15, "minecraft:bone", 1..=6)
+        .entry(15, "minecraft:string", 1..=6)
+        .entry(10, "minecraft:coal", 2..=8)
+        .entry(10, "minecraft:iron_ingot", 1..=4)
+        .entry(5, "minecraft:gold_ingot", 1..=3)
+        .entry_with_meta(5, "minecraft:dye", 4, 2..=6)
+        .entry(3, "minecraft:saddle", 1..=1)
+        .entry(2, "minecraft:name_tag", 1..=1)
+        .entry(1, "minecraft:golden_apple", 1..=1),
+    );
+
+    tables
+  }
+
+  /// Adds a loot table. The name must be unique.
+  pub fn add(&mut self, name: &'static str, table: LootTable) {
+    assert!(!self.tables.contains_key(name), "duplicate loot table {name}");
+
+    self.tables.insert(name, table);
+  }
+
+  /// Fills in the loot of a container placed at `pos`. Containers that
+  /// reference a table that isn't registered here are given a seed, so that
+  /// Minecraft fills them in the same way every time.
+  pub fn resolve(&self, pos: Pos, block_entity: &BlockEntity) -> BlockEntity {
+    let mut resolved = block_entity.clone();
+    let Some(name) = &block_entity.loot_table else { return resolved };
+
+    let mut rng = Rng::new(derive_pos_seed(self.seed, pos));
+    match self.tables.get(name.as_str()) {
+      Some(table) => {
+        resolved.loot_table = None;
+        table.fill(&mut rng, &mut resolved.data);
+      }
+      None => resolved.data.insert("LootTableSeed", rng.next() as i64),
+    }
+
+    resolved
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn containers_are_filled_deterministically() {
+    let tables = LootTables::new(1234);
+    let chest = BlockEntity::new().with_loot_table("rgen:dungeon");
+
+    let a = tables.resolve(Pos::new(10, 20, 30), &chest);
+    let b = tables.resolve(Pos::new(10, 20, 30), &chest);
+    let c = tables.resolve(Pos::new(11, 20, 30), &chest);
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+
+    assert_eq!(a.loot_table, None);
+    let Some(Nbt::List(items)) = a.data.get("Items") else { panic!("no items in {a:?}") };
+    assert!((3..=6).contains(&items.len()));
+
+    let mut slots = items
+      .iter()
+      .map(|item| match item {
+        Nbt::Compound(item) => item.get("Slot").cloned().unwrap(),
+        _ => panic!(),
+      })
+      .map(|slot| slot.to_string())
+      .collect::<Vec<_>>();
+    slots.sort();
+    slots.dedup();
+    assert_eq!(slots.len(), items.len(), "two items in the same slot");
+
+    // Vanilla tables are left for Minecraft to fill in.
+    let vanilla = BlockEntity::new().with_loot_table("minecraft:chests/simple_dungeon");
+    let resolved = tables.resolve(Pos::new(10, 20, 30), &vanilla);
+    assert_eq!(resolved.loot_table, vanilla.loot_table);
+    assert!(resolved.data.get("LootTableSeed").is_some());
+  }
+}
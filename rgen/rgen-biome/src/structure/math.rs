@@ -0,0 +1,15 @@
+use rgen_base::Pos;
+
+pub struct Rectangle {
+  pub min: Pos,
+  pub max: Pos,
+}
+
+impl Rectangle {
+  pub fn intersects(&self, other: &Rectangle) -> bool {
+    self.min.x <= other.max.x
+      && self.max.x >= other.min.x
+      && self.min.z <= other.max.z
+      && self.max.z >= other.min.z
+  }
+}
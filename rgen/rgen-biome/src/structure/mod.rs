@@ -1,22 +1,342 @@
-use rgen_base::{Chunk, ChunkPos};
+//! Structures are large, multi-chunk features, like villages and dungeons.
+//!
+//! Each [`StructureType`] is spread out on its own grid of points, and each
+//! point is the origin of a single structure. Structures are generated in two
+//! passes, just like biomes:
+//! - `generate` runs on a single chunk, and is used for things that need to fit
+//!   into the terrain, like roads.
+//! - `decorate` runs on a [`PartialWorld`], so it can place buildings that span
+//!   multiple chunks.
+//!
+//! Containers in structures should be placed with [`place_block_entity`], so
+//! that their loot gets filled in.
+
+use std::f64::consts::SQRT_2;
+
+use rgen_base::{BlockEntity, Chunk, ChunkPos, Pos};
+use rgen_placer::{derive_pos_seed, derive_seed, grid::PointGrid};
 use rgen_world::{BlockInfoSupplier, PartialWorld};
 
+use crate::{WorldBiomes, feature};
+
+mod cache;
+mod dungeon;
+mod jigsaw;
+mod loot;
+mod math;
 mod village;
 
+pub use loot::LootTable;
+use loot::LootTables;
+
+/// A type of structure. Each structure type is registered on the
+/// [`StructureGenerator`], which takes care of spacing them out and seeding
+/// each structure.
+pub trait StructureType: Send + Sync {
+  /// The average distance between structures of this type, in blocks.
+  fn spacing(&self) -> f64;
+
+  /// The furthest any part of a structure can be from its origin, on the X and
+  /// Z axes.
+  fn radius(&self) -> i32;
+
+  /// Returns true if this structure can spawn with its origin at the given
+  /// position.
+  fn allowed_at(&self, world: &WorldBiomes, origin: Pos) -> bool {
+    let _ = (world, origin);
+    true
+  }
+
+  /// Returns where the structure with the given origin ends up, or `None` if it
+  /// won't be generated. This is used to locate structures, so it should avoid
+  /// laying out the whole structure.
+  fn locate(&self, seed: u64, origin: Pos, world: &WorldBiomes) -> Option<Pos> {
+    let _ = (seed, world);
+    Some(origin)
+  }
+
+  /// The furthest [`locate`](Self::locate) can move a structure away from its
+  /// origin, on the X and Z axes.
+  fn max_locate_offset(&self) -> i32 { 0 }
+
+  /// Places the parts of the structure that are in the given chunk, before the
+  /// chunk is decorated.
+  fn generate(
+    &self,
+    seed: u64,
+    origin: Pos,
+    world: &WorldBiomes,
+    info: &BlockInfoSupplier,
+    chunk: &mut Chunk,
+    chunk_pos: ChunkPos,
+  ) {
+    let _ = (seed, origin, world, info, chunk, chunk_pos);
+  }
+
+  /// Places the parts of the structure that belong to the given chunk, while
+  /// decorating it.
+  fn decorate(
+    &self,
+    seed: u64,
+    origin: Pos,
+    world: &WorldBiomes,
+    partial: &mut PartialWorld,
+    chunk_pos: ChunkPos,
+  ) {
+    let _ = (seed, origin, world, partial, chunk_pos);
+  }
+}
+
+/// All the structures in a world.
 pub struct StructureGenerator {
-  village: village::VillageGenerator,
+  seed:       u64,
+  structures: Vec<StructureBuilder>,
+  loot:       LootTables,
+}
+
+struct StructureBuilder {
+  name:      &'static str,
+  seed:      u64,
+  grid:      PointGrid,
+  structure: Box<dyn StructureType>,
 }
 
 impl StructureGenerator {
   pub fn new(seed: u64) -> Self {
-    StructureGenerator { village: village::VillageGenerator::new(seed) }
+    let mut generator = StructureGenerator {
+      seed,
+      structures: vec![],
+      loot: LootTables::new(derive_seed(seed, "loot")),
+    };
+
+    if feature::VILLAGES {
+      generator.add("village", village::VillageGenerator::new());
+    }
+    if feature::DUNGEONS {
+      generator.add("dungeon", dungeon::DungeonGenerator::new());
+    }
+
+    generator
+  }
+
+  /// Adds a structure type. The name must be unique, as it is used to seed the
+  /// structure.
+  pub fn add(&mut self, name: &'static str, structure: impl StructureType + 'static) {
+    assert!(self.structures.iter().all(|s| s.name != name), "duplicate structure type {name}");
+
+    self.structures.push(StructureBuilder {
+      name,
+      seed: derive_seed(self.seed, name),
+      grid: PointGrid::new(),
+      structure: Box::new(structure),
+    });
+  }
+
+  /// Adds a loot table, which containers can reference by name.
+  pub fn add_loot_table(&mut self, name: &'static str, table: LootTable) {
+    self.loot.add(name, table);
+  }
+
+  /// Returns the names of all the structure types.
+  pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+    self.structures.iter().map(|s| s.name)
   }
 
-  pub fn generate(&self, info: &BlockInfoSupplier, chunk: &mut Chunk, chunk_pos: ChunkPos) {
-    self.village.generate(info, chunk, chunk_pos);
+  /// Finds the closest structure of the given type to `pos`, at most
+  /// `max_radius` blocks away. Returns `None` if there is no such structure, or
+  /// no structure type with that name.
+  pub fn locate(&self, world: &WorldBiomes, name: &str, pos: Pos, max_radius: i32) -> Option<Pos> {
+    profile_function!();
+
+    self.structures.iter().find(|s| s.name == name)?.locate(world, pos, max_radius)
   }
 
-  pub fn decorate(&self, world: &mut PartialWorld, chunk_pos: ChunkPos) {
-    self.village.decorate(world, chunk_pos);
+  pub fn generate(
+    &self,
+    world: &WorldBiomes,
+    info: &BlockInfoSupplier,
+    chunk: &mut Chunk,
+    chunk_pos: ChunkPos,
+  ) {
+    profile_function!();
+
+    for builder in &self.structures {
+      builder.origins_near(world, chunk_pos, |seed, origin| {
+        builder.structure.generate(seed, origin, world, info, chunk, chunk_pos);
+      });
+    }
+  }
+
+  pub fn decorate(&self, world: &WorldBiomes, partial: &mut PartialWorld, chunk_pos: ChunkPos) {
+    profile_function!();
+
+    for builder in &self.structures {
+      builder.origins_near(world, chunk_pos, |seed, origin| {
+        builder.structure.decorate(seed, origin, world, partial, chunk_pos);
+      });
+    }
+  }
+}
+
+/// Places a block entity that is part of a structure, and fills in its loot.
+fn place_block_entity(
+  world: &WorldBiomes,
+  partial: &mut PartialWorld,
+  pos: Pos,
+  block_entity: &BlockEntity,
+) {
+  partial.set_block_entity(pos, world.structure.loot.resolve(pos, block_entity));
+}
+
+impl StructureBuilder {
+  /// Calls `f` with the seed and origin of every structure that could reach
+  /// into the given chunk.
+  fn origins_near(&self, world: &WorldBiomes, chunk_pos: ChunkPos, mut f: impl FnMut(u64, Pos)) {
+    let points = self.grid.points_near_chunk(
+      self.seed,
+      chunk_pos,
+      self.structure.spacing(),
+      self.structure.radius(),
+    );
+
+    for point in points {
+      let origin = Pos::new(point.0 as i32, 0, point.1 as i32);
+
+      if !self.structure.allowed_at(world, origin) {
+        continue;
+      }
+
+      f(derive_pos_seed(self.seed, origin), origin);
+    }
+  }
+
+  /// Searches the grid outward from `pos`, one ring of squares at a time, and
+  /// returns the closest structure that will actually be generated.
+  fn locate(&self, world: &WorldBiomes, pos: Pos, max_radius: i32) -> Option<Pos> {
+    let spacing = self.structure.spacing();
+    let center_x = (f64::from(pos.x) / spacing).floor() as i32;
+    let center_z = (f64::from(pos.z) / spacing).floor() as i32;
+    let max_ring = (f64::from(max_radius) / spacing).ceil() as i32 + 1;
+    let offset = self.structure.max_locate_offset();
+
+    let max_ring = max_ring + (f64::from(offset) / spacing).ceil() as i32;
+    let distance = |other: Pos| f64::from(other.x - pos.x).hypot(f64::from(other.z - pos.z));
+
+    let mut best: Option<(Pos, f64)> = None;
+    for ring in 0..=max_ring {
+      // Every point in this ring is at least `ring - 1` squares away, and
+      // `locate` can move it closer by up to `offset` on each axis, so once
+      // something closer than that has been found, the search is done.
+      let min_dist = f64::from(ring - 1) * spacing - f64::from(offset) * SQRT_2;
+      if best.is_some_and(|(_, dist)| dist <= min_dist) {
+        break;
+      }
+
+      for dx in -ring..=ring {
+        for dz in -ring..=ring {
+          if dx.abs() != ring && dz.abs() != ring {
+            continue;
+          }
+
+          let point = self.grid.point_in_square(self.seed, center_x + dx, center_z + dz);
+          let origin = Pos::new((point.0 * spacing) as i32, 0, (point.1 * spacing) as i32);
+
+          if !self.structure.allowed_at(world, origin) {
+            continue;
+          }
+
+          let Some(found) =
+            self.structure.locate(derive_pos_seed(self.seed, origin), origin, world)
+          else {
+            continue;
+          };
+
+          let dist = distance(found);
+          if dist <= f64::from(max_radius) && best.is_none_or(|(_, best)| dist < best) {
+            best = Some((found, dist));
+          }
+        }
+      }
+    }
+
+    best.map(|(pos, _)| pos)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rgen_world::Context;
+
+  use super::*;
+
+  struct Marker;
+
+  impl StructureType for Marker {
+    fn spacing(&self) -> f64 { 64.0 }
+    fn radius(&self) -> i32 { 0 }
+  }
+
+  /// Moves each structure away from its grid point, like villages do.
+  struct Moved;
+
+  impl StructureType for Moved {
+    fn spacing(&self) -> f64 { 16.0 }
+    fn radius(&self) -> i32 { 0 }
+
+    fn locate(&self, seed: u64, origin: Pos, _: &WorldBiomes) -> Option<Pos> {
+      Some(origin + Pos::new((seed % 97) as i32 - 48, 0, (seed / 97 % 97) as i32 - 48))
+    }
+
+    fn max_locate_offset(&self) -> i32 { 48 }
+  }
+
+  #[test]
+  fn locate_finds_closest() {
+    let ctx = Context::new_test(1234);
+    let mut world = WorldBiomes::new(&ctx.blocks, ctx.seed);
+    world.add_structure("marker", Marker);
+
+    let builder = world.structure.structures.iter().find(|s| s.name == "marker").unwrap();
+
+    for i in 0..16 {
+      let pos = Pos::new(i * 97 - 800, 0, i * -61 + 300);
+      let found = world.locate_structure("marker", pos, 512).unwrap();
+
+      // Check against every structure in a large area around `pos`.
+      let closest = builder
+        .grid
+        .points_in_area(builder.seed, -30.0, -30.0, 30.0, 30.0)
+        .map(|(x, z)| Pos::new((x * 64.0) as i32, 0, (z * 64.0) as i32))
+        .min_by_key(|p| (p.x - pos.x).pow(2) + (p.z - pos.z).pow(2))
+        .unwrap();
+      assert_eq!(found, closest);
+    }
+
+    assert_eq!(world.locate_structure("marker", Pos::new(0, 0, 0), 0), None);
+    assert_eq!(world.locate_structure("missing", Pos::new(0, 0, 0), 512), None);
+  }
+  #[test]
+  fn locate_finds_moved() {
+    let ctx = Context::new_test(1234);
+    let mut world = WorldBiomes::new(&ctx.blocks, ctx.seed);
+    world.add_structure("moved", Moved);
+
+    let builder = world.structure.structures.iter().find(|s| s.name == "moved").unwrap();
+
+    for i in 0..256 {
+      let pos = Pos::new(i * 23 % 512 - 256, 0, i * 41 % 512 - 256);
+      let found = world.locate_structure("moved", pos, 512).unwrap();
+      let dist = |p: Pos| (p.x - pos.x).pow(2) + (p.z - pos.z).pow(2);
+
+      let closest = builder
+        .grid
+        .points_in_area(builder.seed, -30.0, -30.0, 30.0, 30.0)
+        .map(|(x, z)| Pos::new((x * 16.0) as i32, 0, (z * 16.0) as i32))
+        .map(|origin| Moved.locate(derive_pos_seed(builder.seed, origin), origin, &world).unwrap())
+        .min_by_key(|&p| dist(p))
+        .unwrap();
+      // Two structures can be equally close, so only check the distance.
+      assert_eq!(dist(found), dist(closest), "searching from {pos:?}");
+    }
   }
 }
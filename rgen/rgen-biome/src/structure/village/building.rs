@@ -1,7 +1,7 @@
-use rgen_base::Pos;
+use rgen_base::{Direction, Pos};
 use rgen_llama::Structure;
 
-use super::math::{Direction, Rectangle};
+use crate::structure::math::Rectangle;
 
 #[derive(Clone)]
 pub struct Building {
@@ -0,0 +1,40 @@
+orientation horizontal
+
+S: minecraft:sandstone[type=sandstone]
+C: minecraft:sandstone[type=chiseled_sandstone]
+M: minecraft:sandstone[type=smooth_sandstone]
+F: minecraft:sandstone foundation
+
+layer front
+M M M M M
+S S   S S
+S S   S S
+M M M M M
+F F F F F
+==
+
+layer middle
+M M M M M
+S       S
+S       S
+M M M M M
+F F F F F
+==
+
+layer window
+M M M M M
+S       S
+C       C
+M M M M M
+F F F F F
+==
+
+repeat middle
+
+layer back
+M M M M M
+S S S S S
+S S C S S
+M M M M M
+F F F F F
+==
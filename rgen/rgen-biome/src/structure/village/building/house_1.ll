@@ -2,9 +2,12 @@ orientation horizontal
 
 I: minecraft:glass_pane
 P: minecraft:planks
-V: minecraft:log[0]
-H: minecraft:log[4]
-D: minecraft:log[8]
+V: minecraft:log[axis=y,variant=oak]
+H: minecraft:log[axis=x,variant=oak]
+D: minecraft:log[axis=z,variant=oak]
+C: minecraft:chest[facing=north] loot minecraft:chests/village_blacksmith
+E: entity minecraft:villager
+F: minecraft:cobblestone foundation
 
 layer front
 
@@ -13,6 +16,7 @@ V P P P V
 V P   P V
 V P   P V
 V P P P V
+F F F F F
 ==
 
 layer middle
@@ -22,6 +26,7 @@ P       P
 P       P
 P       P
 P P P P P
+F F F F F
 ==
 
 layer window
@@ -31,10 +36,28 @@ P       P
 I       I
 P       P
 P P P P P
+F F F F F
 ==
 
-repeat window
-repeat middle
+layer villager
+  D D D
+D P P P D
+P       P
+I       I
+P   E   P
+P P P P P
+F F F F F
+==
+
+layer storage
+  D D D
+D P P P D
+P       P
+P       P
+P C     P
+P P P P P
+F F F F F
+==
 
 layer back
 
@@ -43,4 +66,5 @@ V P P P V
 V P P P V
 V P P P V
 V P P P V
+F F F F F
 ==
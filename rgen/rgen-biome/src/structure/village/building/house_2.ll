@@ -2,9 +2,10 @@ orientation horizontal
 
 I: minecraft:glass_pane
 P: minecraft:planks
-V: minecraft:log[0]
-H: minecraft:log[4]
-D: minecraft:log[8]
+V: minecraft:log[axis=y,variant=oak]
+H: minecraft:log[axis=x,variant=oak]
+D: minecraft:log[axis=z,variant=oak]
+F: minecraft:cobblestone foundation
 
 layer front
   H H H
@@ -12,6 +13,7 @@ V P P P V
 V P   P V
 V P   P V
 V P P P V
+F F F F F
 ==
 
 layer middle
@@ -20,6 +22,7 @@ D P P P D
 P       P
 P       P
 P P P P P
+F F F F F
 ==
 
 layer window
@@ -28,6 +31,7 @@ D P P P D
 I       I
 P       P
 P P P P P
+F F F F F
 ==
 
 repeat window
@@ -39,4 +43,5 @@ V P P P V
 V P P P V
 V P P P V
 V P P P V
+F F F F F
 ==
@@ -1,39 +1,52 @@
-use math::Direction;
-use rgen_base::{BlockFilter, BlockState, Chunk, ChunkPos, ChunkRelPos, Pos, block, block_kind};
-use rgen_llama::Structure;
-use rgen_placer::{Random, Rng, grid::PointGrid};
+use std::sync::Arc;
+
+use rgen_base::{BlockFilter, BlockState, Chunk, ChunkPos, Direction, Pos, StateId, block};
+use rgen_llama::rotate_block;
+use rgen_placer::{Random, Rng};
 use rgen_world::PartialWorld;
 
 mod building;
-mod math;
 mod road;
+mod style;
 
 use building::Building;
 use rgen_world::BlockInfoSupplier;
 use road::Road;
+use style::{VillageStyle, VillageStyles};
 
-pub struct VillageGenerator {
-  seed: u64,
-  grid: PointGrid,
+use super::{StructureType, cache::LayoutCache, math::Rectangle, place_block_entity};
+use crate::WorldBiomes;
 
+pub struct VillageGenerator {
   replaceable: BlockFilter,
-  road_block:  BlockState,
-
-  buildings: Vec<Structure>,
+  styles:      VillageStyles,
+  layouts:     LayoutCache<Option<Village>>,
 }
 
-const VILLAGE_RADIUS: i32 = 96;
+/// The furthest a road can reach from the center of a village.
+const VILLAGE_RADIUS: i32 = 128;
 
-impl VillageGenerator {
-  pub fn new(seed: u64) -> Self {
-    let grid = PointGrid::new();
+/// How far a village can be moved from its grid point, to find a better site.
+const SITE_SEARCH: i32 = 24;
+/// The radius around a site that must be flat.
+const SITE_RADIUS: i32 = 32;
+/// The most the terrain height can vary around a site.
+const MAX_SITE_SLOPE: f64 = 24.0;
+
+/// The most a road can climb for each block along it, as sampled from the
+/// height map at each end.
+const MAX_ROAD_SLOPE: f64 = 0.5;
 
+/// The most the ground can vary under a building before it is skipped.
+const MAX_FOUNDATION_HEIGHT: i32 = 12;
+
+impl VillageGenerator {
+  pub fn new() -> Self {
     VillageGenerator {
-      seed,
-      grid,
       // FIXME: Needs so much replacing.
       replaceable: [
         block![air],
+        block![water],
         block![leaves],
         block![rgen:leaves],
         block![rgen:leaves2],
@@ -42,172 +55,291 @@ impl VillageGenerator {
         block![tallgrass],
       ]
       .into(),
-      road_block: block![grass_path],
-      buildings: vec![
-        rgen_llama::parse(include_str!("building/house_1.ll")),
-        rgen_llama::parse(include_str!("building/house_2.ll")),
-      ],
+      styles:      VillageStyles::new(),
+      layouts:     LayoutCache::new(),
     }
   }
 
-  fn call_villages(&self, chunk_pos: ChunkPos, mut f: impl FnMut(Village)) {
-    // FIXME: A lot of this is copied from noodle caves, need to dedupe.
+  fn layout(&self, seed: u64, origin: Pos, world: &WorldBiomes) -> Arc<Option<Village>> {
+    self.layouts.get(origin, || Village::new(self, world, seed, origin))
+  }
+}
 
-    let scale = 256.0;
+impl StructureType for VillageGenerator {
+  fn spacing(&self) -> f64 { 256.0 }
 
-    let min_pos = chunk_pos.min_block_pos();
-    let cave_min_x = ((min_pos.x - VILLAGE_RADIUS) as f64) / scale;
-    let cave_min_z = ((min_pos.z - VILLAGE_RADIUS) as f64) / scale;
-    let cave_max_x = ((min_pos.x + 16 + VILLAGE_RADIUS) as f64) / scale;
-    let cave_max_z = ((min_pos.z + 16 + VILLAGE_RADIUS) as f64) / scale;
+  fn radius(&self) -> i32 { VILLAGE_RADIUS + SITE_SEARCH + 16 }
 
-    let points =
-      self.grid.points_in_area(self.seed, cave_min_x, cave_min_z, cave_max_x, cave_max_z);
-    for point in points {
-      let pos = Pos::new((point.0 * scale) as i32, 0, (point.1 * scale) as i32);
+  fn allowed_at(&self, world: &WorldBiomes, origin: Pos) -> bool {
+    self.styles.at(world, origin).is_some()
+  }
 
-      // A seed unique to this village.
-      let village_seed = self.seed ^ ((pos.x as u64) << 8) ^ ((pos.y as u64) << 16);
+  fn locate(&self, _: u64, origin: Pos, world: &WorldBiomes) -> Option<Pos> {
+    let site = choose_site(world, origin)?;
+    self.styles.at(world, site)?;
 
-      let village = Village::new(self, village_seed, pos);
-      f(village)
-    }
+    Some(site.with_y(world.surface_height(site)))
   }
 
-  pub fn generate(&self, info: &BlockInfoSupplier, chunk: &mut Chunk, chunk_pos: ChunkPos) {
-    self.call_villages(chunk_pos, |village| {
+  fn max_locate_offset(&self) -> i32 { SITE_SEARCH }
+
+  fn generate(
+    &self,
+    seed: u64,
+    origin: Pos,
+    world: &WorldBiomes,
+    info: &BlockInfoSupplier,
+    chunk: &mut Chunk,
+    chunk_pos: ChunkPos,
+  ) {
+    if let Some(village) = &*self.layout(seed, origin, world) {
       village.generate(info, chunk, chunk_pos);
-    });
+    }
   }
 
-  pub fn decorate(&self, world: &mut PartialWorld, chunk_pos: ChunkPos) {
-    self.call_villages(chunk_pos, |village| {
-      village.decorate(world, chunk_pos);
-    });
+  fn decorate(
+    &self,
+    seed: u64,
+    origin: Pos,
+    world: &WorldBiomes,
+    partial: &mut PartialWorld,
+    chunk_pos: ChunkPos,
+  ) {
+    if let Some(village) = &*self.layout(seed, origin, world) {
+      village.decorate(self, world, partial, chunk_pos);
+    }
   }
 }
 
-struct Village<'a> {
-  generator: &'a VillageGenerator,
+struct Village {
+  style: Arc<VillageStyle>,
 
-  roads:     Vec<Road>,
-  buildings: Vec<Building>,
+  roads:        Vec<Road>,
+  /// The heights along each road, from [`Road::heights`].
+  road_heights: Vec<Vec<i32>>,
+  buildings:    Vec<Building>,
 }
 
-impl<'a> Village<'a> {
-  pub fn new(generator: &'a VillageGenerator, seed: u64, origin: Pos) -> Self {
-    let mut village = Village { generator, roads: vec![], buildings: vec![] };
+impl Village {
+  /// Lays out a village near `origin`. Returns `None` if there is nowhere to
+  /// put it.
+  pub fn new(
+    generator: &VillageGenerator,
+    world: &WorldBiomes,
+    seed: u64,
+    origin: Pos,
+  ) -> Option<Self> {
+    let origin = choose_site(world, origin)?;
+    let style = generator.styles.at(world, origin)?.clone();
+
+    let mut village = Village { style, roads: vec![], road_heights: vec![], buildings: vec![] };
 
     let mut rng = Rng::new(seed);
-    village.recursive_road(&mut rng, origin, 0);
+    village.recursive_road(&mut rng, world, origin, 0);
+    village.road_heights = village.roads.iter().map(|road| road.heights(world)).collect();
 
     village.place_buildings(&mut rng);
 
-    village
+    Some(village)
   }
 
   pub fn generate(&self, info: &BlockInfoSupplier, chunk: &mut Chunk, chunk_pos: ChunkPos) {
-    for road in &self.roads {
-      for x in road.min().x - 1..=road.max().x + 1 {
-        for z in road.min().z - 1..=road.max().z + 1 {
-          let pos = Pos::new(x, 100, z);
+    let min = chunk_pos.min_block_pos();
+    let chunk_box = Rectangle { min, max: min + Pos::new(15, 0, 15) };
+
+    let water = info.encode(block![water]);
+
+    for (road, heights) in self.roads.iter().zip(&self.road_heights) {
+      if !road.bounding_box().intersects(&chunk_box) {
+        continue;
+      }
+
+      let dir = road.dir();
+      let side = dir.right().dir();
 
+      for (i, center) in road.positions().enumerate() {
+        let y = heights[i];
+        let prev = if i > 0 { heights[i - 1] } else { y };
+        let next = heights.get(i + 1).copied().unwrap_or(y);
+
+        for offset in -1..=1 {
+          let pos = center + side * offset;
           if !pos.in_chunk(chunk_pos) {
             continue;
           }
-
           let rel = pos.chunk_rel();
 
-          let y = highest_block(chunk, rel).y();
-
-          let replacing = info.decode(chunk.get(rel.with_y(y)));
-          // Intersections get placed multiple times, so we need to check for planks here
-          // too.
-          let placing = if replacing == block![water] || replacing == block![planks] {
-            block![planks]
+          // Stairs go on the higher block of each step, facing uphill.
+          let placing = if chunk.get(rel.with_y(y)) == water {
+            self.style.bridge
+          } else if y > prev && y <= next {
+            stairs(self.style.stairs, dir)
+          } else if y > next && y <= prev {
+            stairs(self.style.stairs, dir.opposite())
           } else {
-            self.generator.road_block
+            self.style.road
           };
           chunk.set(rel.with_y(y), info.encode(placing));
+
+          // Cut through hills.
+          for y in y + 1..=y + 3 {
+            if chunk.get(rel.with_y(y)) != water {
+              chunk.set(rel.with_y(y), StateId::AIR);
+            }
+          }
+
+          // Fill in dips, unless this is a bridge.
+          if placing != self.style.bridge {
+            let mut y = y - 1;
+            while y > 0 && chunk.get(rel.with_y(y)) == StateId::AIR {
+              chunk.set(rel.with_y(y), info.encode(block![dirt]));
+              y -= 1;
+            }
+          }
         }
       }
     }
   }
 
-  pub fn decorate(&self, world: &mut PartialWorld, chunk_pos: ChunkPos) {
+  pub fn decorate(
+    &self,
+    generator: &VillageGenerator,
+    world_biomes: &WorldBiomes,
+    world: &mut PartialWorld,
+    chunk_pos: ChunkPos,
+  ) {
     for building in &self.buildings {
-      let structure = &self.generator.buildings[building.building_id as usize];
-
       // If the building is in this chunk, we place it. Because this is part of the
       // decoration pass, we can modify blocks in neighboring chunks. So we'll
       // place the entire building at once, and we can consistently find ground
       // level at the same time.
-      if !building.pos.in_chunk(chunk_pos) {
-        continue;
+      if building.pos.in_chunk(chunk_pos) {
+        self.place_building(generator, world_biomes, world, building);
       }
+    }
+  }
 
-      // The Y position of the base of the building.
-      let mut max_height = 0;
-      let mut min_height = 255;
-      for x in 0..structure.width() {
-        for z in 0..structure.depth() {
-          let rel_pos = Pos::new(x as i32, 0, z as i32);
-          let pos = building.transform_to_world(structure, rel_pos);
-
-          for y in (0..=255).rev() {
-            if !self.generator.replaceable.contains(world.get(pos.with_y(y))) {
-              if y < min_height {
-                min_height = y;
-              }
-              if y > max_height {
-                max_height = y;
-              }
-              break;
-            }
-          }
-        }
+  fn place_building(
+    &self,
+    generator: &VillageGenerator,
+    world_biomes: &WorldBiomes,
+    world: &mut PartialWorld,
+    building: &Building,
+  ) {
+    let structure = &self.style.buildings[building.building_id as usize];
+
+    let mut ground = vec![];
+    for x in 0..structure.width() as i32 {
+      for z in 0..structure.depth() as i32 {
+        let pos = building.transform_to_world(structure, Pos::new(x, 0, z));
+        ground.push((pos, ground_height(generator, world, pos)));
       }
+    }
 
-      // If the ground is too steep, don't place the building.
-      if max_height - min_height > 5 {
-        continue;
-      }
+    let min_height = ground.iter().map(|g| g.1).min().unwrap_or(0);
+    let max_height = ground.iter().map(|g| g.1).max().unwrap_or(0);
 
-      for rel_pos in structure.blocks() {
-        let block = structure.get(rel_pos);
-        // NB: The building is placed at `max_height` to set it into the surface by 1
-        // block.
-        let pos = building.transform_to_world(structure, rel_pos + Pos::new(0, max_height, 0));
+    // If the ground is too steep, don't place the building.
+    if max_height - min_height > MAX_FOUNDATION_HEIGHT {
+      return;
+    }
 
-        if block != block![air] {
-          world.set(pos, rotate_block(block, building.forward));
-        }
+    // The Y position of the floor of the building. This replaces the top block of
+    // the ground, to set the building into the surface by 1 block.
+    let floor =
+      (ground.iter().map(|g| g.1).sum::<i32>() as f64 / ground.len() as f64).round() as i32;
+
+    // Cut into the hill, so the building isn't buried.
+    for &(pos, height) in &ground {
+      for y in floor + 1..=height {
+        world.set(pos.with_y(y), block![air]);
       }
+    }
 
-      // Fill in some dirt below the foundation.
-      for x in 0..structure.width() {
-        for z in 0..structure.depth() {
-          let rel_pos = Pos::new(x as i32, 0, z as i32);
-          let pos = building.transform_to_world(structure, rel_pos);
+    // Buildings sit on a layer of foundation cells, which fill in the ground below
+    // them.
+    let base = floor - 1;
 
-          let mut y = max_height - 1;
-          while self.generator.replaceable.contains(world.get(pos.with_y(y))) {
-            world.set(pos.with_y(y), block![dirt]);
-            y -= 1;
-          }
+    for rel_pos in structure.blocks() {
+      let block = structure.get(rel_pos);
+      let pos = building.transform_to_world(structure, rel_pos + Pos::new(0, base, 0));
 
-          // Set the layer below to dirt (this is usually grass, which we want to
-          // replace).
-          world.set(pos.with_y(y), block![dirt]);
-        }
+      world.place_structure_block(
+        pos,
+        rotate_block(block, building.forward),
+        structure.placement(rel_pos),
+        &generator.replaceable,
+      );
+    }
+
+    for (rel_pos, block_entity) in structure.block_entities() {
+      let pos = building.transform_to_world(structure, *rel_pos + Pos::new(0, base, 0));
+      place_block_entity(world_biomes, world, pos, block_entity);
+    }
+    for (rel_pos, entity) in structure.entities() {
+      let pos = building.transform_to_world(structure, *rel_pos + Pos::new(0, base, 0));
+      world.add_entity(pos, entity.clone());
+    }
+  }
+}
+
+/// Returns the Y level of the ground at the given column, ignoring plants and
+/// water.
+fn ground_height(generator: &VillageGenerator, world: &PartialWorld, pos: Pos) -> i32 {
+  for y in (0..=255).rev() {
+    if !generator.replaceable.contains(world.get(pos.with_y(y))) {
+      return y;
+    }
+  }
+
+  0
+}
+
+/// Picks the flattest spot near `origin` that is above sea level. Returns
+/// `None` if there's nowhere flat enough.
+fn choose_site(world: &WorldBiomes, origin: Pos) -> Option<Pos> {
+  let mut best: Option<(Pos, f64)> = None;
+
+  for dx in [-SITE_SEARCH, 0, SITE_SEARCH] {
+    for dz in [-SITE_SEARCH, 0, SITE_SEARCH] {
+      let site = origin + Pos::new(dx, 0, dz);
+
+      let mut min = f64::MAX;
+      let mut max = f64::MIN;
+      for (x, z) in [(0, 0), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)] {
+        let height = world.sample_height(site + Pos::new(x, 0, z) * SITE_RADIUS);
+        min = min.min(height);
+        max = max.max(height);
+      }
+
+      // Villages can't be built in the sea.
+      if min < 64.0 {
+        continue;
+      }
+
+      if best.is_none_or(|(_, slope)| max - min < slope) {
+        best = Some((site, max - min));
       }
     }
   }
+
+  best.filter(|&(_, slope)| slope <= MAX_SITE_SLOPE).map(|(site, _)| site)
+}
+
+fn stairs(stairs: BlockState, facing: Direction) -> BlockState {
+  let facing = match facing {
+    Direction::North => "north",
+    Direction::East => "east",
+    Direction::South => "south",
+    Direction::West => "west",
+  };
+
+  stairs.with_prop("facing", facing)
 }
 
 // Village generation
-impl<'a> Village<'a> {
-  fn recursive_road(&mut self, rng: &mut Rng, pos: Pos, depth: u32) {
+impl Village {
+  fn recursive_road(&mut self, rng: &mut Rng, world: &WorldBiomes, pos: Pos, depth: u32) {
     if depth > 3 {
       return;
     }
@@ -234,10 +366,16 @@ impl<'a> Village<'a> {
         continue;
       }
 
+      // Don't run roads up cliffs.
+      let climb = (world.sample_height(new_pos) - world.sample_height(pos)).abs();
+      if climb > length as f64 * MAX_ROAD_SLOPE {
+        continue;
+      }
+
       let road = Road { start: pos, end: new_pos };
       self.roads.push(road);
 
-      self.recursive_road(rng, new_pos, depth + 1);
+      self.recursive_road(rng, world, new_pos, depth + 1);
     }
   }
 
@@ -260,8 +398,8 @@ impl<'a> Village<'a> {
             continue;
           }
 
-          let building_id = rng.range(0..self.generator.buildings.len() as i32) as u32;
-          let building = &self.generator.buildings[building_id as usize];
+          let building_id = rng.range(0..self.style.buildings.len() as i32) as u32;
+          let building = &self.style.buildings[building_id as usize];
 
           let forward = if side { off_axis.positive_dir() } else { off_axis.negative_dir() };
 
@@ -300,38 +438,3 @@ impl<'a> Village<'a> {
     true
   }
 }
-
-fn highest_block(chunk: &Chunk, pos: ChunkRelPos) -> ChunkRelPos {
-  let mut y = 255;
-
-  // TODO: A better air check?
-  while chunk.get(pos.with_y(y)).0 == 0 {
-    y -= 1;
-  }
-
-  ChunkRelPos::new(pos.x(), y, pos.z())
-}
-
-fn rotate_block(block: BlockState, dir: Direction) -> BlockState {
-  fn rotate_ccw(block: BlockState) -> BlockState {
-    let state = block.state.state().unwrap_or_default();
-
-    let new_state = match block.block {
-      // axis=x -> axis=z
-      block_kind![log] if state & 0b1100 == 0b0100 => state & 0b0011 | 0b1000,
-      // axis=z -> axis=x
-      block_kind![log] if state & 0b1100 == 0b1000 => state & 0b0011 | 0b0100,
-
-      _ => return block,
-    };
-
-    block.with_data(new_state)
-  }
-
-  match dir {
-    Direction::North => block,
-    Direction::East => rotate_ccw(block),
-    Direction::South => rotate_ccw(rotate_ccw(block)),
-    Direction::West => rotate_ccw(rotate_ccw(rotate_ccw(block))),
-  }
-}
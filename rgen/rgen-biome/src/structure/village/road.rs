@@ -1,6 +1,12 @@
-use rgen_base::Pos;
+use rgen_base::{Axis, Direction, Pos};
 
-use super::math::{Axis, Rectangle};
+use crate::{WorldBiomes, structure::math::Rectangle};
+
+/// The top of the water roads are placed on when crossing the sea.
+const SEA_LEVEL: i32 = 63;
+
+/// How far on each side the terrain is averaged when smoothing out a road.
+const SMOOTH_RADIUS: usize = 3;
 
 #[derive(Clone, Copy)]
 pub struct Road {
@@ -11,6 +17,51 @@ pub struct Road {
 impl Road {
   pub fn axis(&self) -> Axis { if self.start.x == self.end.x { Axis::Z } else { Axis::X } }
 
+  /// Returns the direction from the start to the end of this road.
+  pub fn dir(&self) -> Direction {
+    let axis = self.axis();
+    let (start, end) = match axis {
+      Axis::X => (self.start.x, self.end.x),
+      Axis::Z => (self.start.z, self.end.z),
+    };
+    if end > start { axis.positive_dir() } else { axis.negative_dir() }
+  }
+
+  /// Returns every position along the center of the road, from start to end.
+  pub fn positions(&self) -> impl Iterator<Item = Pos> {
+    let (start, dir) = (self.start, self.dir().dir());
+    let len = (self.end.x - self.start.x).abs() + (self.end.z - self.start.z).abs();
+    (0..=len).map(move |i| start + dir * i)
+  }
+
+  /// Returns the Y level of the road surface at each of the `positions`. The
+  /// road follows the terrain, smoothed out so that it never steps up or down
+  /// more than one block at a time. Over water, the road sits at sea level.
+  pub fn heights(&self, world: &WorldBiomes) -> Vec<i32> {
+    let terrain =
+      self.positions().map(|pos| world.surface_height(pos).max(SEA_LEVEL)).collect::<Vec<_>>();
+
+    let smooth = (0..terrain.len())
+      .map(|i| {
+        let window =
+          &terrain[i.saturating_sub(SMOOTH_RADIUS)..(i + SMOOTH_RADIUS + 1).min(terrain.len())];
+        (window.iter().sum::<i32>() as f64 / window.len() as f64).round() as i32
+      })
+      .collect::<Vec<_>>();
+
+    // The highest and lowest a road can be at each position, while only stepping
+    // one block at a time. Going halfway between them cuts half of each slope
+    // into the hill, and fills in the other half.
+    (0..smooth.len())
+      .map(|i| {
+        let dist = |j: usize| i.abs_diff(j) as i32;
+        let cut = smooth.iter().enumerate().map(|(j, h)| h + dist(j)).min().unwrap();
+        let fill = smooth.iter().enumerate().map(|(j, h)| h - dist(j)).max().unwrap();
+        (cut + fill).div_euclid(2)
+      })
+      .collect()
+  }
+
   /// Returns the minimum center of this road. Note that the bounding box
   /// extends beyond this position.
   pub fn min(&self) -> Pos {
@@ -28,3 +79,27 @@ impl Road {
     Rectangle { min: self.min() - Pos::new(1, 0, 1), max: self.max() + Pos::new(1, 0, 1) }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use rgen_world::Context;
+
+  use super::*;
+
+  #[test]
+  fn roads_step_one_block() {
+    let ctx = Context::new_test(1234);
+    let world = WorldBiomes::new(&ctx.blocks, ctx.seed);
+
+    for i in 0..16 {
+      let start = Pos::new(i * 200, 0, -i * 100);
+      for end in [start + Pos::new(32, 0, 0), start - Pos::new(0, 0, 32)] {
+        let road = Road { start, end };
+        let heights = road.heights(&world);
+
+        assert_eq!(heights.len(), road.positions().count());
+        assert!(heights.windows(2).all(|w| (w[0] - w[1]).abs() <= 1), "{heights:?}");
+      }
+    }
+  }
+}
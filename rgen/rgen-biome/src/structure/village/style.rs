@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use rgen_base::{BlockState, Pos, block, block_kind};
+use rgen_llama::Structure;
+
+use crate::{ClimateType, GeographicType, WorldBiomes};
+
+/// The blocks and buildings a village is built from. Each village picks a
+/// style based on the biome at its center.
+pub struct VillageStyle {
+  pub road:   BlockState,
+  /// Placed instead of the road over water.
+  pub bridge: BlockState,
+  /// Placed where the road steps up a block. The `facing` property is set to
+  /// the uphill direction.
+  pub stairs: BlockState,
+
+  pub buildings: Vec<Structure>,
+}
+
+pub struct VillageStyles {
+  plains: Arc<VillageStyle>,
+  taiga:  Arc<VillageStyle>,
+  desert: Arc<VillageStyle>,
+}
+
+impl VillageStyles {
+  pub fn new() -> Self {
+    let houses = [
+      rgen_llama::parse(include_str!("building/house_1.ll")).unwrap(),
+      rgen_llama::parse(include_str!("building/house_2.ll")).unwrap(),
+    ];
+
+    VillageStyles {
+      plains: Arc::new(VillageStyle {
+        road:      block![grass_path],
+        bridge:    block![planks[0]],
+        stairs:    block![oak_stairs[half = "bottom"]],
+        buildings: houses.to_vec(),
+      }),
+      taiga:  Arc::new(VillageStyle {
+        road:      block![grass_path],
+        bridge:    block![planks[1]],
+        stairs:    block![spruce_stairs[half = "bottom"]],
+        buildings: houses
+          .iter()
+          .map(|house| {
+            replace_blocks(
+              house,
+              &[
+                (block![planks[0]], block![planks[1]]),
+                (
+                  block![log[axis = "y", variant = "oak"]],
+                  block![log[axis = "y", variant = "spruce"]],
+                ),
+                (
+                  block![log[axis = "x", variant = "oak"]],
+                  block![log[axis = "x", variant = "spruce"]],
+                ),
+                (
+                  block![log[axis = "z", variant = "oak"]],
+                  block![log[axis = "z", variant = "spruce"]],
+                ),
+              ],
+            )
+          })
+          .collect(),
+      }),
+      desert: Arc::new(VillageStyle {
+        road:      block![sandstone[2]],
+        bridge:    block![planks[4]],
+        stairs:    block![sandstone_stairs[half = "bottom"]],
+        buildings: vec![
+          rgen_llama::parse(include_str!("building/desert_house_1.ll")).unwrap(),
+          rgen_llama::parse(include_str!("building/desert_house_2.ll")).unwrap(),
+        ],
+      }),
+    }
+  }
+
+  /// Returns the style for a village centered at `pos`, or `None` if villages
+  /// can't spawn there.
+  pub fn at(&self, world: &WorldBiomes, pos: Pos) -> Option<&Arc<VillageStyle>> {
+    // Water and rough terrain are no place for a village, and neither is the
+    // ice cap.
+    let unsuitable = matches!(
+      world.geographic_type(pos),
+      GeographicType::MushroomIsland
+        | GeographicType::Ocean
+        | GeographicType::Beach
+        | GeographicType::Canyon
+        | GeographicType::River
+        | GeographicType::Mountains
+    ) || world.climate_type(pos) == ClimateType::IceCap;
+    if unsuitable {
+      return None;
+    }
+
+    let biome = world.choose_surface_biome(pos);
+    let top = biome.top_block().block;
+    if top == block_kind![sand] {
+      Some(&self.desert)
+    } else if top == block_kind![snow_layer]
+      || ["fir", "spruce", "windswept"].iter().any(|name| biome.name.contains(name))
+    {
+      Some(&self.taiga)
+    } else {
+      Some(&self.plains)
+    }
+  }
+}
+
+/// Returns a copy of the structure, with some blocks swapped out.
+fn replace_blocks(structure: &Structure, replace: &[(BlockState, BlockState)]) -> Structure {
+  let mut replaced = structure.clone();
+  for pos in structure.blocks() {
+    let block = structure.get(pos);
+    if let Some((_, to)) = replace.iter().find(|(from, _)| *from == block) {
+      replaced.set(pos, *to);
+    }
+  }
+  replaced
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt, str::FromStr};
 
 use crate::{biome::*, builder::BiomeBuilder};
 
@@ -16,39 +16,50 @@ macro_rules! b {
 pub struct CompositionLookup {
   pub blank:  BiomeComposition,
   pub lookup: HashMap<(GeographicType, ClimateType), BiomeComposition>,
+  pub caves:  HashMap<(CaveDepth, CaveClimate), BiomeComposition>,
 }
 
 macro_rules! biome_categories {
   (
-    fn build() {
+    fn $name:ident() -> $key:ty {
       $(
-        let ($geographic:expr, $climate:expr) = &[$($biome:expr),* $(,)?];
+        let ($a:expr, $b:expr) = &[$($biome:expr),* $(,)?];
       )*
     }
   ) => {
-    impl CompositionLookup {
-      pub fn new(seed: u64) -> CompositionLookup {
-        let mut lookup = HashMap::new();
-        $(
-          if lookup.insert(($geographic, $climate), composition(seed, &[$($biome),*])).is_some() {
-            panic!("Duplicate biome for {:?}, {:?}", $geographic, $climate);
-          }
-        )*
-        CompositionLookup { blank: composition(seed, &[b!(1, blank)]), lookup }
-      }
+    fn $name(seed: u64) -> HashMap<$key, BiomeComposition> {
+      let mut lookup = HashMap::new();
+      $(
+        if lookup.insert(($a, $b), composition(seed, &[$($biome),*])).is_some() {
+          panic!("Duplicate biome for {:?}, {:?}", $a, $b);
+        }
+      )*
+      lookup
     }
   };
 }
 
 impl CompositionLookup {
+  pub fn new(seed: u64) -> CompositionLookup {
+    CompositionLookup {
+      blank:  composition(seed, &[b!(1, blank)]),
+      lookup: surface(seed),
+      caves:  caves(seed),
+    }
+  }
+
   pub fn choose(&self, geographic: GeographicType, climate: ClimateType) -> &BiomeComposition {
     self.lookup.get(&(geographic, climate)).unwrap_or(&self.blank)
   }
+
+  pub fn choose_cave(&self, depth: CaveDepth, climate: CaveClimate) -> &BiomeComposition {
+    self.caves.get(&(depth, climate)).unwrap_or(&self.blank)
+  }
 }
 
 // === Biome categories ===
 biome_categories!(
-  fn build() {
+  fn surface() -> (GeographicType, ClimateType) {
     // IceCap vvv
 
     let (GeographicType::Standard, ClimateType::IceCap) =
@@ -120,6 +131,41 @@ biome_categories!(
   }
 );
 
+// === Cave biome categories ===
+biome_categories!(
+  fn caves() -> (CaveDepth, CaveClimate) {
+    // Shallow vvv
+
+    let (CaveDepth::Shallow, CaveClimate::Frozen) = &[b!(20, frozen_cave)];
+
+    let (CaveDepth::Shallow, CaveClimate::Temperate) = &[b!(15, cave), b!(5, dripstone_cave)];
+
+    let (CaveDepth::Shallow, CaveClimate::Lush) = &[b!(15, lush_cave), b!(5, cave)];
+
+    let (CaveDepth::Shallow, CaveClimate::Hot) = &[b!(10, dripstone_cave), b!(10, cave)];
+
+    // Deep vvv
+
+    let (CaveDepth::Deep, CaveClimate::Frozen) = &[b!(10, frozen_cave), b!(10, cave)];
+
+    let (CaveDepth::Deep, CaveClimate::Temperate) = &[b!(10, cave), b!(10, dripstone_cave)];
+
+    let (CaveDepth::Deep, CaveClimate::Lush) = &[b!(20, lush_cave)];
+
+    let (CaveDepth::Deep, CaveClimate::Hot) = &[b!(10, dripstone_cave), b!(10, magma_cave)];
+
+    // Core vvv
+
+    let (CaveDepth::Core, CaveClimate::Frozen) = &[b!(20, cave)];
+
+    let (CaveDepth::Core, CaveClimate::Temperate) = &[b!(15, cave), b!(5, magma_cave)];
+
+    let (CaveDepth::Core, CaveClimate::Lush) = &[b!(10, lush_cave), b!(10, cave)];
+
+    let (CaveDepth::Core, CaveClimate::Hot) = &[b!(20, magma_cave)];
+  }
+);
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum GeographicType {
   MushroomIsland,
@@ -149,6 +195,83 @@ pub enum ClimateType {
   Tropical,
 }
 
+/// An error from parsing a [`GeographicType`] or [`ClimateType`] with an
+/// unknown name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownTypeError {
+  /// The kind of type, like `geographic`.
+  pub kind: &'static str,
+  pub name: String,
+}
+
+impl fmt::Display for UnknownTypeError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "unknown {} type '{}'", self.kind, self.name)
+  }
+}
+
+impl std::error::Error for UnknownTypeError {}
+
+impl FromStr for GeographicType {
+  type Err = UnknownTypeError;
+
+  /// Parses a snake case name, like `mountains`.
+  fn from_str(s: &str) -> Result<Self, UnknownTypeError> {
+    Ok(match s {
+      "mushroom_island" => GeographicType::MushroomIsland,
+      "ocean" => GeographicType::Ocean,
+      "beach" => GeographicType::Beach,
+      "canyon" => GeographicType::Canyon,
+      "river" => GeographicType::River,
+      "standard" => GeographicType::Standard,
+      "hills" => GeographicType::Hills,
+      "mountains" => GeographicType::Mountains,
+      _ => return Err(UnknownTypeError { kind: "geographic", name: s.into() }),
+    })
+  }
+}
+
+impl FromStr for ClimateType {
+  type Err = UnknownTypeError;
+
+  /// Parses a snake case name, like `warm_temperate`.
+  fn from_str(s: &str) -> Result<Self, UnknownTypeError> {
+    Ok(match s {
+      "ice_cap" => ClimateType::IceCap,
+      "tundra" => ClimateType::Tundra,
+      "sub_arctic" => ClimateType::SubArctic,
+      "cool_temperate" => ClimateType::CoolTemperate,
+      "dry_temperate" => ClimateType::DryTemperate,
+      "warm_temperate" => ClimateType::WarmTemperate,
+      "wet_temperate" => ClimateType::WetTemperate,
+      "mediterranean" => ClimateType::Mediterranean,
+      "monsoon" => ClimateType::Monsoon,
+      "savanna" => ClimateType::Savanna,
+      "hot_desert" => ClimateType::HotDesert,
+      "bad_lands" => ClimateType::BadLands,
+      "tropical" => ClimateType::Tropical,
+      _ => return Err(UnknownTypeError { kind: "climate", name: s.into() }),
+    })
+  }
+}
+
+/// How far underground a cave biome is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CaveDepth {
+  Shallow,
+  Deep,
+  /// Right above bedrock, around the lava seas.
+  Core,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CaveClimate {
+  Frozen,
+  Temperate,
+  Lush,
+  Hot,
+}
+
 use ClimateType::*;
 #[rustfmt::skip]
 pub const CLIMATE_TABLE: ClimateTable = &[
@@ -176,4 +299,15 @@ mod tests {
 
   #[test]
   fn composition() { CompositionLookup::new(0); }
+
+  #[test]
+  fn parse_types() {
+    assert_eq!("mountains".parse(), Ok(GeographicType::Mountains));
+    assert_eq!("warm_temperate".parse(), Ok(ClimateType::WarmTemperate));
+
+    let err = "volcano".parse::<GeographicType>().unwrap_err();
+    assert_eq!(err.to_string(), "unknown geographic type 'volcano'");
+    let err = "arctic".parse::<ClimateType>().unwrap_err();
+    assert_eq!(err.to_string(), "unknown climate type 'arctic'");
+  }
 }
@@ -0,0 +1,128 @@
+//! Per-chunk caches of the terrain noise maps.
+//!
+//! Generating a chunk needs the height of each column several times (for the
+//! stone, the top layer, and the chunk placers), so those are sampled once up
+//! front. Optionally, the 3D density map can also be sampled on a coarse
+//! lattice and interpolated, instead of being sampled for every block. See
+//! [`feature::INTERPOLATED_DENSITY`].
+
+use rgen_base::{ChunkPos, Pos};
+use rgen_placer::noise::NoiseGenerator3D;
+
+use crate::{HeightInfo, WorldBiomes, feature};
+
+/// The width of a single cell of the density lattice, on the X and Z axes.
+const CELL_WIDTH: i32 = 4;
+/// The height of a single cell of the density lattice.
+const CELL_HEIGHT: i32 = 8;
+
+// The number of lattice points on each axis. There is one more point than
+// cells, so that the last cell has an edge to interpolate towards.
+const POINTS_XZ: usize = (16 / CELL_WIDTH) as usize + 1;
+const POINTS_Y: usize = (256 / CELL_HEIGHT) as usize + 1;
+
+/// The cached terrain for a single chunk.
+pub struct ChunkTerrain {
+  chunk_pos: ChunkPos,
+
+  /// The `max_height` of each column, indexed by `[x][z]`.
+  heights: [[f64; 16]; 16],
+  density: Option<DensityLattice>,
+}
+
+/// A coarse lattice of the 3D density map, which is trilinearly interpolated
+/// to get the density at each block.
+pub struct DensityLattice {
+  /// Indexed by `[x][z][y]`, so that each column can be sampled at once.
+  values: Box<[[[f64; POINTS_Y]; POINTS_XZ]; POINTS_XZ]>,
+}
+
+impl ChunkTerrain {
+  pub fn new(world: &WorldBiomes, chunk_pos: ChunkPos) -> Self {
+    profile_function!();
+
+    let mut heights = [[0.0; 16]; 16];
+    for (x, column) in heights.iter_mut().enumerate() {
+      for (z, height) in column.iter_mut().enumerate() {
+        *height = world.sample_height(chunk_pos.min_block_pos() + Pos::new(x as i32, 0, z as i32));
+      }
+    }
+
+    let density = if feature::INTERPOLATED_DENSITY {
+      Some(DensityLattice::new(world, chunk_pos))
+    } else {
+      None
+    };
+
+    ChunkTerrain { chunk_pos, heights, density }
+  }
+
+  /// Returns the height info for the given column in this chunk. This is the
+  /// same as [`WorldBiomes::height_info`], but it won't sample any of the
+  /// height maps.
+  ///
+  /// Panics if the column is outside of this chunk.
+  pub fn height_info<'a>(&'a self, world: &'a WorldBiomes, pos: Pos) -> HeightInfo<'a> {
+    HeightInfo::new_cached(world, pos, self.max_height(pos).unwrap(), self)
+  }
+
+  /// Returns the cached `max_height` at the given column, or `None` if the
+  /// column is outside of this chunk.
+  pub fn max_height(&self, pos: Pos) -> Option<f64> {
+    if pos.in_chunk(self.chunk_pos) {
+      let rel = pos.chunk_rel();
+      Some(self.heights[rel.x() as usize][rel.z() as usize])
+    } else {
+      None
+    }
+  }
+
+  pub fn density(&self) -> Option<&DensityLattice> { self.density.as_ref() }
+}
+
+impl DensityLattice {
+  fn new(world: &WorldBiomes, chunk_pos: ChunkPos) -> Self {
+    profile_function!();
+
+    let mut values = Box::new([[[0.0; POINTS_Y]; POINTS_XZ]; POINTS_XZ]);
+    for (x, row) in values.iter_mut().enumerate() {
+      for (z, column) in row.iter_mut().enumerate() {
+        let pos =
+          chunk_pos.min_block_pos() + Pos::new(x as i32 * CELL_WIDTH, 0, z as i32 * CELL_WIDTH);
+
+        world.density_map.generate_column_3d(
+          pos.x as f64,
+          0.0,
+          pos.z as f64,
+          CELL_HEIGHT as f64,
+          column,
+        );
+      }
+    }
+
+    DensityLattice { values }
+  }
+
+  /// Samples the density at the given position. The position must be in the
+  /// chunk this lattice was built for. Positions outside of the world are
+  /// clamped to the top or bottom of the lattice.
+  pub fn sample(&self, pos: Pos) -> f64 {
+    let rel = pos.chunk_rel();
+    let (x, z) = (i32::from(rel.x()), i32::from(rel.z()));
+    let y = pos.y.clamp(0, 255);
+
+    let (x0, xd) = ((x / CELL_WIDTH) as usize, (x % CELL_WIDTH) as f64 / CELL_WIDTH as f64);
+    let (y0, yd) = ((y / CELL_HEIGHT) as usize, (y % CELL_HEIGHT) as f64 / CELL_HEIGHT as f64);
+    let (z0, zd) = ((z / CELL_WIDTH) as usize, (z % CELL_WIDTH) as f64 / CELL_WIDTH as f64);
+
+    let v = &self.values;
+    let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+
+    let v00 = lerp(v[x0][z0][y0], v[x0 + 1][z0][y0], xd);
+    let v10 = lerp(v[x0][z0][y0 + 1], v[x0 + 1][z0][y0 + 1], xd);
+    let v01 = lerp(v[x0][z0 + 1][y0], v[x0 + 1][z0 + 1][y0], xd);
+    let v11 = lerp(v[x0][z0 + 1][y0 + 1], v[x0 + 1][z0 + 1][y0 + 1], xd);
+
+    lerp(lerp(v00, v10, yd), lerp(v01, v11, yd), zd)
+  }
+}
@@ -60,3 +60,19 @@ fn bench_base(b: &mut Bencher) {
     generator.generate_base(&context, &mut chunk, chunk_pos);
   });
 }
+
+/// Generates the same chunk every time. This varies much less than
+/// `bench_base`, so it's better for comparing changes to terrain generation.
+#[bench]
+fn bench_base_single_chunk(b: &mut Bencher) {
+  let context = Arc::new(rgen_world::Context::new_test(1));
+  let generator = Arc::new(WorldBiomes::new(&context.blocks, context.seed));
+
+  let chunk_pos = ChunkPos::new(3, 5);
+
+  b.iter(|| {
+    let mut chunk = Chunk::new();
+    generator.generate_base(&context, &mut chunk, chunk_pos);
+    test::black_box(&chunk);
+  });
+}
@@ -4,15 +4,22 @@ use std::cell::RefCell;
 
 use jni::{
   JNIEnv,
-  objects::{JByteArray, JCharArray, JClass, JValue},
-  sys::{jbyte, jint, jlong, jobject, jobjectArray, jstring},
+  objects::{JByteArray, JCharArray, JClass, JObject, JObjectArray, JString, JValue},
+  sys::{jbyte, jint, jintArray, jlong, jobject, jobjectArray, jstring},
 };
 use rgen_world::PartialWorldStorage;
 
 use crate::{ctx::Context, lookup_biome_info, lookup_block_info};
-use rgen_base::{BiomeId, ChunkPos, Pos, StateId};
+use rgen_base::{BiomeId, ChunkPos, ChunkRelPos, Pos, StateId};
+use rgen_biome::{ClimateType, GeographicType};
 use rgen_spline::Cosine;
 
+/// The largest radius, in blocks, that the biome search functions will search.
+/// The search time grows with the square of the radius, so this stops a huge
+/// radius from Java from hanging the server. This is the same limit as the
+/// vanilla `/locatebiome` command.
+const MAX_FIND_RADIUS: jint = 6400;
+
 #[allow(dead_code)]
 struct JniWorldStorage<'a, 'b: 'a> {
   env: RefCell<&'a mut JNIEnv<'b>>,
@@ -112,24 +119,57 @@ pub extern "system" fn Java_net_macmv_rgen_rust_RustGenerator_wait_1for_1log(
   }
 }
 
+/// Fills in the blocks of a chunk. Returns a `ChunkExtras` with the block
+/// entities and entities in the chunk, or `null` if there aren't any.
 #[unsafe(no_mangle)]
 pub extern "system" fn Java_net_macmv_rgen_rust_RustGenerator_build_1chunk(
-  env: JNIEnv,
+  mut env: JNIEnv,
   _class: JClass,
   data: JCharArray,
   chunk_x: jint,
   chunk_z: jint,
-) {
+) -> jobject {
   let len = env.get_array_length(&data).unwrap();
   assert_eq!(len, 65536, "data array must be 65536 elements long");
 
-  Context::run(|ctx| {
+  let chunk_pos = ChunkPos::new(chunk_x, chunk_z);
+
+  let (block_entities, entities) = Context::run(|ctx| {
     puffin::GlobalProfiler::lock().new_frame();
 
-    ctx.world.generate(ChunkPos::new(chunk_x, chunk_z), |chunk| {
+    ctx.world.generate(chunk_pos, |chunk| {
       env.set_char_array_region(data, 0, chunk.data()).unwrap();
-    });
+
+      let min = chunk_pos.min_block_pos();
+      let rel_to_pos = |rel: ChunkRelPos| min + Pos::new(rel.x().into(), rel.y(), rel.z().into());
+
+      let block_entities: Vec<String> = chunk
+        .block_entities()
+        .iter()
+        .map(|(rel, block_entity)| block_entity.to_nbt(rel_to_pos(*rel)).to_string())
+        .collect();
+      let entities: Vec<String> = chunk
+        .entities()
+        .iter()
+        .map(|(rel, entity)| entity.to_nbt(rel_to_pos(*rel)).to_string())
+        .collect();
+
+      (block_entities, entities)
+    })
   });
+
+  if block_entities.is_empty() && entities.is_empty() {
+    return std::ptr::null_mut();
+  }
+
+  let block_entities = string_array(&mut env, &block_entities);
+  let entities = string_array(&mut env, &entities);
+
+  let obj = env.new_object("net/macmv/rgen/rust/ChunkExtras", "()V", &[]).unwrap();
+  env.set_field(&obj, "blockEntities", "[Ljava/lang/String;", (&block_entities).into()).unwrap();
+  env.set_field(&obj, "entities", "[Ljava/lang/String;", (&entities).into()).unwrap();
+
+  obj.into_raw()
 }
 
 #[unsafe(no_mangle)]
@@ -281,6 +321,100 @@ pub extern "system" fn Java_net_macmv_rgen_rust_RustGenerator_get_1biome_1name_1
   env.new_string(biome).unwrap().as_raw()
 }
 
+/// Finds the closest structure with the given name. Returns the X, Y, and Z of
+/// the structure, or `null` if there isn't one within `max_radius` blocks.
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_net_macmv_rgen_rust_RustGenerator_locate_1structure(
+  mut env: JNIEnv,
+  _class: JClass,
+  name: JString,
+  block_x: jint,
+  block_z: jint,
+  max_radius: jint,
+) -> jintArray {
+  let name: String = env.get_string(&name).unwrap().into();
+  let pos = Pos::new(block_x, 0, block_z);
+
+  let found = Context::run(|ctx| ctx.generator.locate_structure(&name, pos, max_radius));
+
+  pos_to_array(&env, found)
+}
+
+/// Finds the closest column with the given surface biome. Returns the X, Y, and
+/// Z of the top of the terrain there, or `null` if there isn't one within
+/// `radius` blocks. `radius` is clamped to `MAX_FIND_RADIUS`.
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_net_macmv_rgen_rust_RustGenerator_find_1biome(
+  mut env: JNIEnv,
+  _class: JClass,
+  name: JString,
+  block_x: jint,
+  block_z: jint,
+  radius: jint,
+) -> jintArray {
+  let name: String = env.get_string(&name).unwrap().into();
+  let pos = Pos::new(block_x, 0, block_z);
+  let radius = radius.clamp(0, MAX_FIND_RADIUS);
+
+  let found = Context::run(|ctx| ctx.generator.find_biome(&name, pos, radius));
+
+  pos_to_array(&env, found)
+}
+
+/// Like `find_biome`, but looks for a geographic and climate type, given as
+/// snake case names (like `hills` and `warm_temperate`). Returns `null` if
+/// either name is invalid.
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_net_macmv_rgen_rust_RustGenerator_find_1biome_1type(
+  mut env: JNIEnv,
+  _class: JClass,
+  geographic: JString,
+  climate: JString,
+  block_x: jint,
+  block_z: jint,
+  radius: jint,
+) -> jintArray {
+  let geographic: String = env.get_string(&geographic).unwrap().into();
+  let climate: String = env.get_string(&climate).unwrap().into();
+  let pos = Pos::new(block_x, 0, block_z);
+  let radius = radius.clamp(0, MAX_FIND_RADIUS);
+
+  let (geographic, climate) =
+    match (geographic.parse::<GeographicType>(), climate.parse::<ClimateType>()) {
+      (Ok(geographic), Ok(climate)) => (geographic, climate),
+      (Err(e), _) | (_, Err(e)) => {
+        log::warn!("{e}");
+        return std::ptr::null_mut();
+      }
+    };
+
+  let found = Context::run(|ctx| ctx.generator.find_biome_type(geographic, climate, pos, radius));
+
+  pos_to_array(&env, found)
+}
+
+fn string_array<'a>(env: &mut JNIEnv<'a>, strings: &[String]) -> JObjectArray<'a> {
+  let mut arr =
+    env.new_object_array(strings.len() as i32, "java/lang/String", JObject::null()).unwrap();
+  for (i, s) in strings.iter().enumerate() {
+    let s = env.new_string(s).unwrap();
+    env.set_object_array_element(&mut arr, i as i32, s).unwrap();
+  }
+  arr
+}
+
+/// Converts a position to an `int[]` of X, Y, and Z, or `null` for `None`.
+fn pos_to_array(env: &JNIEnv, pos: Option<Pos>) -> jintArray {
+  match pos {
+    Some(pos) => {
+      let arr = env.new_int_array(3).unwrap();
+      env.set_int_array_region(&arr, 0, &[pos.x, pos.y, pos.z]).unwrap();
+      arr.as_raw()
+    }
+    None => std::ptr::null_mut(),
+  }
+}
+
 // This is for re-loading the generator.
 #[unsafe(no_mangle)]
 pub extern "system" fn rgen_get_seed() -> u64 { Context::run(|ctx| ctx.context.seed) }
@@ -7,8 +7,8 @@ use std::{ffi::CStr, process::Command};
 
 use jni::{
   JNIEnv,
-  objects::{JByteArray, JCharArray, JClass, JValue},
-  sys::{jbyte, jint, jlong, jobject, jobjectArray, jstring},
+  objects::{JByteArray, JCharArray, JClass, JString, JValue},
+  sys::{jbyte, jint, jintArray, jlong, jobject, jobjectArray, jstring},
 };
 use libc::{LM_ID_NEWLM, RTLD_LOCAL, RTLD_NOW, c_void, dlclose, dlerror, dlmopen, dlsym};
 use parking_lot::RwLock;
@@ -89,7 +89,7 @@ functions! {
     data: JCharArray,
     chunk_x: jint,
     chunk_z: jint,
-  ) -> ();
+  ) -> jobject;
 
   fn Java_net_macmv_rgen_rust_RustGenerator_build_1biomes(
     env: JNIEnv,
@@ -131,6 +131,34 @@ functions! {
     block_y: jint,
     block_z: jint,
   ) -> jstring;
+
+  fn Java_net_macmv_rgen_rust_RustGenerator_locate_1structure(
+    env: JNIEnv,
+    class: JClass,
+    name: JString,
+    block_x: jint,
+    block_z: jint,
+    max_radius: jint,
+  ) -> jintArray;
+
+  fn Java_net_macmv_rgen_rust_RustGenerator_find_1biome(
+    env: JNIEnv,
+    class: JClass,
+    name: JString,
+    block_x: jint,
+    block_z: jint,
+    radius: jint,
+  ) -> jintArray;
+
+  fn Java_net_macmv_rgen_rust_RustGenerator_find_1biome_1type(
+    env: JNIEnv,
+    class: JClass,
+    geographic: JString,
+    climate: JString,
+    block_x: jint,
+    block_z: jint,
+    radius: jint,
+  ) -> jintArray;
 }
 
 #[unsafe(no_mangle)]
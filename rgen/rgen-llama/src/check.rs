@@ -0,0 +1,62 @@
+//! Checks structure files ahead of time. Structures are included with
+//! `include_str!` and parsed when the world generator starts, so a typo would
+//! otherwise only show up as a crash in the game.
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+/// Parses every structure file in `dir`, and panics with all the errors if any
+/// of them are invalid. This is meant to be called from a build script, so
+/// that a broken structure fails the build:
+///
+/// ```no_run
+/// // In `main` of build.rs:
+/// rgen_llama::check_structures("src");
+/// ```
+pub fn check_structures(dir: impl AsRef<Path>) {
+  let dir = dir.as_ref();
+  println!("cargo::rerun-if-changed={}", dir.display());
+
+  let files = find_structures(dir);
+  for file in &files {
+    println!("cargo::rerun-if-changed={}", file.display());
+  }
+
+  let errors = structure_errors(&files);
+  if !errors.is_empty() {
+    panic!("invalid structures:\n{}", errors.join("\n\n"));
+  }
+}
+
+/// Finds all the `.ll` files in `dir` and its subdirectories.
+pub(crate) fn find_structures(dir: &Path) -> Vec<PathBuf> {
+  fn find(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+      let path = entry.unwrap().path();
+      if path.is_dir() && path.file_name().unwrap() != "target" {
+        find(&path, files);
+      } else if path.extension().is_some_and(|ext| ext == "ll") {
+        files.push(path);
+      }
+    }
+  }
+
+  let mut files = vec![];
+  find(dir, &mut files);
+  files.sort();
+  files
+}
+
+/// Parses each file, and returns the errors, prefixed with the file they came
+/// from.
+pub(crate) fn structure_errors(files: &[PathBuf]) -> Vec<String> {
+  files
+    .iter()
+    .filter_map(|path| {
+      let err = crate::parse(&fs::read_to_string(path).unwrap()).err()?;
+      Some(format!("{}: {err}", path.display()))
+    })
+    .collect()
+}
@@ -0,0 +1,41 @@
+use std::fmt;
+
+/// An error in a structure file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+  pub message: String,
+  /// The line of the error, starting at 1.
+  pub line:    usize,
+  /// The column of the error, in characters, starting at 1.
+  pub column:  usize,
+  /// The contents of the line the error is on.
+  pub snippet: String,
+}
+
+impl ParseError {
+  /// Creates an error pointing at the byte offset `pos` in `input`.
+  pub(crate) fn new(input: &str, pos: usize, message: impl Into<String>) -> Self {
+    let line_start = input[..pos].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = input[pos..].find('\n').map_or(input.len(), |i| pos + i);
+
+    ParseError {
+      message: message.into(),
+      line:    input[..pos].matches('\n').count() + 1,
+      column:  input[line_start..pos].chars().count() + 1,
+      snippet: input[line_start..line_end].into(),
+    }
+  }
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let gutter = self.line.to_string().len();
+
+    writeln!(f, "{} at {}:{}", self.message, self.line, self.column)?;
+    writeln!(f, "{:gutter$} |", "")?;
+    writeln!(f, "{} | {}", self.line, self.snippet)?;
+    write!(f, "{:gutter$} | {:>column$}", "", "^", column = self.column)
+  }
+}
+
+impl std::error::Error for ParseError {}
@@ -1,3 +1,5 @@
+mod check;
+mod error;
 mod parser;
 mod structure;
 
@@ -6,12 +8,21 @@ mod tests;
 
 use std::collections::HashMap;
 
-use rgen_base::{BlockKind, Pos};
-pub use structure::Structure;
+pub use check::check_structures;
+pub use error::ParseError;
+use rgen_base::{BlockEntity, BlockState, Direction, Entity, Pos};
+pub use structure::{Connector, Placement, Structure, rotate_block};
 
 #[derive(Default, Debug)]
 struct Ast {
-  names: HashMap<char, BlockName>,
+  names:       HashMap<char, BlockState>,
+  connectors:  HashMap<char, ConnectorName>,
+  /// Entity ids, like `minecraft:villager`.
+  entities:    HashMap<char, String>,
+  /// Loot tables for the containers in `names`.
+  loot_tables: HashMap<char, String>,
+  /// How the blocks in `names` are placed, if not `Placement::Normal`.
+  placements:  HashMap<char, Placement>,
 
   layers:      HashMap<LayerKey, Layer>,
   ordered:     Vec<LayerKey>,
@@ -36,21 +47,31 @@ struct Layer {
   width:  u32,
   height: u32,
 
-  blocks: Vec<char>,
+  blocks:  Vec<char>,
+  /// Where each block is in the source, for error messages.
+  offsets: Vec<usize>,
 }
 
 #[derive(Debug, Clone)]
-struct BlockName {
-  category: String,
-  block:    String,
-  state:    Option<u32>,
+struct ConnectorName {
+  tag:   String,
+  block: Option<BlockState>,
 }
 
-pub fn parse(input: &str) -> Structure {
+impl Ast {
+  fn is_defined(&self, c: char) -> bool {
+    self.names.contains_key(&c)
+      || self.connectors.contains_key(&c)
+      || self.entities.contains_key(&c)
+  }
+}
+
+/// Parses a structure file.
+pub fn parse(input: &str) -> Result<Structure, ParseError> {
   let mut parser = parser::Parser::new(input);
   let mut ast = Ast::default();
 
-  parser.parse(&mut ast);
+  parser.parse(&mut ast)?;
 
   let width = ast.layers.values().map(|layer| layer.width).max().unwrap_or(0);
   let height = ast.layers.values().map(|layer| layer.height).max().unwrap_or(0);
@@ -62,31 +83,65 @@ pub fn parse(input: &str) -> Structure {
     let layer = ast.layers.get(name).unwrap();
     for y in 0..layer.height {
       for x in 0..layer.width {
-        let block = layer.blocks[(y * layer.width + x) as usize];
+        let index = (y * layer.width + x) as usize;
+        let block = layer.blocks[index];
         if block == ' ' {
           continue;
         }
 
-        let name = ast.names.get(&block).unwrap();
-        let block_name = format!("{}:{}", name.category, name.block);
-
-        let block =
-          BlockKind::by_name(&block_name).unwrap_or_else(|| panic!("no such block {block_name}"));
-
-        let state = match name.state {
-          Some(state) => block.with_data(state as u8),
-          None => block.with_data(0),
-        };
-
         let pos = match ast.orientation {
           Orientation::Horizontal => Pos::new(x as i32, y as i32, z as i32),
           Orientation::Vertical => Pos::new(x as i32, z as i32, y as i32),
         };
 
-        structure.set(pos, state);
+        if let Some(connector) = ast.connectors.get(&block) {
+          let facing = connector_facing(&structure, pos).ok_or_else(|| {
+            parser.err_at(
+              layer.offsets[index],
+              "connector must be on exactly one side of the structure",
+            )
+          })?;
+          structure.add_connector(Connector { pos, facing, tag: connector.tag.clone() });
+
+          if let Some(state) = connector.block {
+            structure.set(pos, state);
+          }
+          continue;
+        }
+
+        if let Some(id) = ast.entities.get(&block) {
+          structure.add_entity(pos, Entity::new(id.as_str()));
+          continue;
+        }
+
+        structure.set(pos, ast.names[&block]);
+        if let Some(&placement) = ast.placements.get(&block) {
+          structure.set_placement(pos, placement);
+        }
+
+        if let Some(loot_table) = ast.loot_tables.get(&block) {
+          structure.set_block_entity(pos, BlockEntity::new().with_loot_table(loot_table.as_str()));
+        }
       }
     }
   }
 
-  structure
+  Ok(structure)
+}
+
+/// Connectors face out of the side of the structure they are on, so they must
+/// be on exactly one of the four sides.
+fn connector_facing(structure: &Structure, pos: Pos) -> Option<Direction> {
+  let sides = [
+    (pos.z == 0, Direction::North),
+    (pos.x == structure.width() as i32 - 1, Direction::East),
+    (pos.z == structure.depth() as i32 - 1, Direction::South),
+    (pos.x == 0, Direction::West),
+  ];
+
+  let mut facing = sides.iter().filter(|(on_side, _)| *on_side).map(|(_, dir)| *dir);
+  match (facing.next(), facing.next()) {
+    (Some(dir), None) => Some(dir),
+    _ => None,
+  }
 }
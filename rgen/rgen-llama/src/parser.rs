@@ -1,4 +1,6 @@
-use crate::{Ast, BlockName, Layer, LayerKey, Orientation};
+use rgen_base::{BlockKind, BlockState, PropType, PropValue, StateOrProps};
+
+use crate::{Ast, ConnectorName, Layer, LayerKey, Orientation, ParseError, Placement};
 
 pub struct Parser<'a> {
   input: &'a str,
@@ -7,10 +9,12 @@ pub struct Parser<'a> {
   seen_orientation: bool,
 }
 
+type Result<T> = std::result::Result<T, ParseError>;
+
 impl<'a> Parser<'a> {
   pub fn new(input: &'a str) -> Self { Parser { input, pos: 0, seen_orientation: false } }
 
-  pub fn parse(&mut self, ast: &mut Ast) {
+  pub fn parse(&mut self, ast: &mut Ast) -> Result<()> {
     loop {
       if self.peek() == '\n' {
         self.next();
@@ -18,7 +22,7 @@ impl<'a> Parser<'a> {
       }
 
       if self.peek() == '#' {
-        while self.peek() != '\n' {
+        while self.peek() != '\n' && self.peek() != '\0' {
           self.next();
         }
         continue;
@@ -26,23 +30,24 @@ impl<'a> Parser<'a> {
 
       if self.peek() == '\0' {
         if !self.seen_orientation {
-          self.err("missing orientation declaration");
+          return Err(self.err("missing orientation declaration"));
         }
 
         break;
       }
 
-      let word = self.next_word();
+      let start = self.pos;
+      let word = self.next_word()?;
       match word.as_str() {
-        "layer" => self.parse_layer(ast),
-        "repeat" => self.parse_repeat(ast),
-        "orientation" => self.parse_orientation(ast),
+        "layer" => self.parse_layer(ast)?,
+        "repeat" => self.parse_repeat(ast)?,
+        "orientation" => self.parse_orientation(ast)?,
         _ => {
-          if word.len() != 1 {
-            self.err("expected single character");
+          if word.chars().count() != 1 {
+            return Err(self.err_at(start, "expected single character"));
           }
 
-          self.parse_name(ast, word.chars().next().unwrap());
+          self.parse_name(ast, word.chars().next().unwrap())?;
         }
       }
 
@@ -51,12 +56,14 @@ impl<'a> Parser<'a> {
       } else if self.pos == self.input.len() {
         break;
       } else {
-        self.err("expected newline");
+        return Err(self.err("expected newline"));
       }
     }
+
+    Ok(())
   }
 
-  fn parse_layer(&mut self, ast: &mut Ast) {
+  fn parse_layer(&mut self, ast: &mut Ast) -> Result<()> {
     self.skip_whitespace();
     let name = self.next_word_opt();
 
@@ -64,31 +71,33 @@ impl<'a> Parser<'a> {
     let mut rows = vec![];
 
     if self.peek() != '\n' {
-      self.err("expected newline");
+      return Err(self.err("expected newline"));
     }
 
     'outer: loop {
       let mut row = vec![];
       loop {
+        let start = self.pos;
         match self.next() {
           '\n' => break,
           '=' if self.peek() == '=' => {
             self.next();
             break 'outer;
           }
+          '\0' => return Err(self.err("expected `==` at the end of the layer")),
           c => {
-            if c != ' ' && !ast.names.contains_key(&c) {
-              self.err(format!("unknown block '{c}'"));
+            if c != ' ' && !ast.is_defined(c) {
+              return Err(self.err_at(start, format!("unknown block '{c}'")));
             }
 
-            row.push(c);
+            row.push((c, start));
           }
         }
 
         match self.next() {
           ' ' => {}
           '\n' => break,
-          _ => self.err("expected space or newline"),
+          _ => return Err(self.err("expected space or newline")),
         }
       }
 
@@ -105,80 +114,200 @@ impl<'a> Parser<'a> {
     let height = rows.len() as u32;
 
     let mut blocks = vec![];
+    let mut offsets = vec![];
     for row in rows {
       for i in 0..width {
-        blocks.push(row.get(i as usize).copied().unwrap_or(' '));
+        let (block, offset) = row.get(i as usize).copied().unwrap_or((' ', 0));
+        blocks.push(block);
+        offsets.push(offset);
       }
     }
 
     let key = match name {
       Some(name) => {
         if ast.layers.contains_key(&LayerKey::Name(name.clone())) {
-          self.err(format!("duplicate layer '{name}'"));
+          return Err(self.err(format!("duplicate layer '{name}'")));
         }
 
         LayerKey::Name(name)
       }
       None => LayerKey::Ord(ast.ordered.len() as u64),
     };
-    ast.layers.insert(key.clone(), Layer { width, height, blocks });
+    ast.layers.insert(key.clone(), Layer { width, height, blocks, offsets });
     ast.ordered.push(key);
+
+    Ok(())
   }
 
-  fn parse_repeat(&mut self, ast: &mut Ast) {
+  fn parse_repeat(&mut self, ast: &mut Ast) -> Result<()> {
     self.skip_whitespace();
 
-    let layer = self.next_word();
+    let start = self.pos;
+    let layer = self.next_word()?;
     let key = LayerKey::Name(layer.clone());
 
     if !ast.layers.contains_key(&key) {
-      self.err(format!("unknown layer '{layer}'"));
+      return Err(self.err_at(start, format!("unknown layer '{layer}'")));
     }
 
     ast.ordered.push(key);
+
+    Ok(())
   }
 
-  fn parse_orientation(&mut self, ast: &mut Ast) {
+  fn parse_orientation(&mut self, ast: &mut Ast) -> Result<()> {
     self.skip_whitespace();
 
-    let orientation = self.next_word();
+    let start = self.pos;
+    let orientation = self.next_word()?;
 
     if self.seen_orientation {
-      self.err("duplicate orientation declaration");
+      return Err(self.err_at(start, "duplicate orientation declaration"));
     }
 
     match orientation.as_str() {
       "vertical" => ast.orientation = Orientation::Vertical,
       "horizontal" => ast.orientation = Orientation::Horizontal,
-      _ => self.err(format!("unknown orientation '{orientation}'")),
+      _ => return Err(self.err_at(start, format!("unknown orientation '{orientation}'"))),
     }
 
     self.seen_orientation = true;
+
+    Ok(())
   }
 
-  fn parse_name(&mut self, ast: &mut Ast, name: char) {
+  fn parse_name(&mut self, ast: &mut Ast, name: char) -> Result<()> {
     self.skip_whitespace();
-    if self.next() != ':' {
-      self.err("expected `:`");
-    }
+    self.expect(':')?;
     self.skip_whitespace();
 
-    let category = self.next_word();
-    if self.next() != ':' {
-      self.err("expected `:`");
-    }
-    let block = self.next_word();
+    let word = self.next_word()?;
+    if word == "connector" {
+      self.skip_whitespace();
+      let tag = self.next_word()?;
+      self.skip_whitespace();
 
-    let state = if self.peek() == '[' {
-      self.next();
-      let state = self.next_number();
-      self.next();
-      Some(state)
+      // The block placed in the connector is optional, and defaults to air.
+      let block = if self.peek() == '\n' || self.peek() == '\0' {
+        None
+      } else {
+        let category = self.next_word()?;
+        Some(self.parse_block(category)?)
+      };
+
+      ast.connectors.insert(name, ConnectorName { tag, block });
+    } else if word == "entity" {
+      self.skip_whitespace();
+      let id = self.next_path()?;
+
+      ast.entities.insert(name, id);
+    } else if word == "keep" {
+      ast.names.insert(name, BlockState::AIR);
+      ast.placements.insert(name, Placement::Keep);
     } else {
-      None
+      let block = self.parse_block(word)?;
+      ast.names.insert(name, block);
+
+      // Blocks can be followed by a placement rule, and containers can be filled
+      // with a loot table.
+      loop {
+        self.skip_whitespace();
+        if self.peek() == '\n' || self.peek() == '\0' {
+          break;
+        }
+
+        let start = self.pos;
+        let option = self.next_word()?;
+        let placement = match option.as_str() {
+          "loot" => {
+            self.skip_whitespace();
+            ast.loot_tables.insert(name, self.next_path()?);
+            continue;
+          }
+          "force" => Placement::Force,
+          "if_replaceable" => Placement::IfReplaceable,
+          "foundation" => Placement::Foundation,
+          _ => return Err(self.err_at(start, format!("unknown option '{option}'"))),
+        };
+
+        if ast.placements.insert(name, placement).is_some() {
+          return Err(self.err_at(start, "duplicate placement rule"));
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Parses a block, like `minecraft:log`, `minecraft:log[4]`, or
+  /// `minecraft:log[axis=x,variant=oak]`. The category has already been read.
+  fn parse_block(&mut self, category: String) -> Result<BlockState> {
+    let start = self.pos - category.len();
+    self.expect(':')?;
+    let name = format!("{category}:{}", self.next_word()?);
+    let Some(kind) = BlockKind::by_name(&name) else {
+      return Err(self.err_at(start, format!("unknown block '{name}'")));
     };
 
-    ast.names.insert(name, BlockName { category, block, state });
+    if self.peek() != '[' {
+      return Ok(kind.with_data(0));
+    }
+    self.next();
+
+    if self.peek().is_ascii_digit() {
+      let data_start = self.pos;
+      let data = self.next_number()?;
+      if data >= 16 {
+        return Err(self.err_at(data_start, format!("block data {data} must be less than 16")));
+      }
+      self.expect(']')?;
+
+      return Ok(kind.with_data(data as u8));
+    }
+
+    let expected = kind.expected_props();
+    let mut state = BlockState { block: kind, state: StateOrProps::Default };
+    loop {
+      let key_start = self.pos;
+      let key = self.next_word()?;
+      let Some(ty) = expected.get(&key) else {
+        return Err(self.err_at(key_start, format!("unknown property '{key}' for block {name}")));
+      };
+      self.expect('=')?;
+
+      let value_start = self.pos;
+      let value = self.next_word()?;
+      let parsed = match ty {
+        PropType::Bool => value.parse().ok().map(PropValue::Bool),
+        PropType::Int(..) => value.parse().ok().map(PropValue::Int),
+        PropType::Enum(_) => Some(PropValue::Enum(&value)),
+      };
+      match parsed {
+        Some(parsed) if ty.matches(&parsed) => state.set_prop(&key, parsed),
+        _ => {
+          return Err(self.err_at(
+            value_start,
+            format!("invalid value '{value}' for property '{key}' (expected {ty:?})"),
+          ));
+        }
+      }
+
+      match self.next() {
+        ',' => {}
+        ']' => break,
+        _ => return Err(self.err("expected `,` or `]`")),
+      }
+    }
+
+    Ok(state)
+  }
+
+  fn expect(&mut self, c: char) -> Result<()> {
+    if self.peek() != c {
+      return Err(self.err(format!("expected `{c}`")));
+    }
+    self.next();
+    Ok(())
   }
 
   fn next(&mut self) -> char {
@@ -187,7 +316,6 @@ impl<'a> Parser<'a> {
     ch
   }
 
-  #[track_caller]
   fn next_word_opt(&mut self) -> Option<String> {
     let start = self.pos;
     while matches!(self.peek(), 'a'..='z' | 'A'..='Z' | '_' | '0'..='9') {
@@ -197,17 +325,29 @@ impl<'a> Parser<'a> {
     if start == self.pos { None } else { Some(self.input[start..self.pos].into()) }
   }
 
-  #[track_caller]
-  fn next_word(&mut self) -> String {
-    self.next_word_opt().unwrap_or_else(|| self.err("expected word"))
+  fn next_word(&mut self) -> Result<String> {
+    self.next_word_opt().ok_or_else(|| self.err("expected word"))
+  }
+
+  /// Reads a resource location, like `minecraft:chests/village_blacksmith`.
+  fn next_path(&mut self) -> Result<String> {
+    let start = self.pos;
+    while !self.peek().is_whitespace() && self.peek() != '\0' {
+      self.next();
+    }
+
+    if start == self.pos {
+      return Err(self.err("expected path"));
+    }
+    Ok(self.input[start..self.pos].into())
   }
 
-  fn next_number(&mut self) -> u32 {
+  fn next_number(&mut self) -> Result<u32> {
     let start = self.pos;
     while self.peek().is_ascii_digit() {
       self.next();
     }
-    self.input[start..self.pos].parse().unwrap()
+    self.input[start..self.pos].parse().map_err(|_| self.err_at(start, "expected number"))
   }
 
   fn peek(&self) -> char { self.input[self.pos..].chars().next().unwrap_or('\0') }
@@ -218,11 +358,10 @@ impl<'a> Parser<'a> {
     }
   }
 
-  #[track_caller]
-  fn err(&self, msg: impl Into<String>) -> ! {
-    let line = self.input[..self.pos].lines().count();
-    let col = self.input[..self.pos].lines().last().unwrap().len();
+  fn err(&self, msg: impl Into<String>) -> ParseError { self.err_at(self.pos, msg) }
 
-    panic!("{} at {line}:{col}", msg.into());
+  /// Returns an error pointing at the byte offset `pos`.
+  pub fn err_at(&self, pos: usize, msg: impl Into<String>) -> ParseError {
+    ParseError::new(self.input, pos, msg)
   }
 }
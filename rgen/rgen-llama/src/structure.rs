@@ -1,6 +1,8 @@
-use rgen_base::{BlockState, BlocksIterExclusive, Pos};
+use rgen_base::{
+  BlockEntity, BlockState, BlocksIterExclusive, Direction, Entity, Pos, PropValue, block_kind,
+};
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Structure {
   // Width on the X-axis
   width:  u32,
@@ -10,13 +12,59 @@ pub struct Structure {
   depth:  u32,
 
   // Block storage, indexed by Y, then Z, then X
-  storage: Vec<BlockState>,
+  storage:    Vec<BlockState>,
+  // How each block is placed, indexed the same as `storage`
+  placements: Vec<Placement>,
+
+  connectors: Vec<Connector>,
+
+  block_entities: Vec<(Pos, BlockEntity)>,
+  entities:       Vec<(Pos, Entity)>,
+}
+
+/// How a block in a structure is placed into the world.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+  /// Replaces the block in the world, unless this block is air.
+  #[default]
+  Normal,
+  /// Leaves the block in the world alone.
+  Keep,
+  /// Always replaces the block in the world, even with air.
+  Force,
+  /// Only replaces the block in the world if it is replaceable, like air or
+  /// grass.
+  IfReplaceable,
+  /// Replaces the block in the world, and then fills the column below with the
+  /// same block down to the ground.
+  Foundation,
+}
+
+/// A point on the side of a structure, where another structure with a
+/// connector of the same tag can be attached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connector {
+  /// The relative position of the connector, which is always on the edge of
+  /// the structure.
+  pub pos:    Pos,
+  /// The side of the structure the connector is on.
+  pub facing: Direction,
+  pub tag:    String,
 }
 
 impl Structure {
   #[cfg(test)]
   fn new_test(width: u32, height: u32, depth: u32, storage: Vec<BlockState>) -> Self {
-    Structure { width, height, depth, storage }
+    Structure {
+      width,
+      height,
+      depth,
+      placements: vec![Placement::Normal; storage.len()],
+      storage,
+      connectors: vec![],
+      block_entities: vec![],
+      entities: vec![],
+    }
   }
 
   pub fn new(width: u32, height: u32, depth: u32) -> Self {
@@ -25,6 +73,10 @@ impl Structure {
       height,
       depth,
       storage: vec![BlockState::AIR; (width * height * depth) as usize],
+      placements: vec![Placement::Normal; (width * height * depth) as usize],
+      connectors: vec![],
+      block_entities: vec![],
+      entities: vec![],
     }
   }
 
@@ -37,6 +89,38 @@ impl Structure {
   /// Returns the depth of the structure, or the number of blocks on the Z-axis.
   pub fn depth(&self) -> u32 { self.depth }
 
+  /// Returns all the connectors on this structure.
+  pub fn connectors(&self) -> &[Connector] { &self.connectors }
+
+  /// Adds a connector to the structure.
+  pub fn add_connector(&mut self, connector: Connector) { self.connectors.push(connector); }
+
+  /// Returns all the block entities in this structure, and their relative
+  /// positions.
+  pub fn block_entities(&self) -> &[(Pos, BlockEntity)] { &self.block_entities }
+
+  /// Sets the block entity at the given relative position, replacing any
+  /// existing one. Panics if the position is outside the structure.
+  pub fn set_block_entity(&mut self, pos: Pos, block_entity: BlockEntity) {
+    assert!(self.contains(pos), "position {pos:?} is out of bounds");
+
+    match self.block_entities.iter_mut().find(|(p, _)| *p == pos) {
+      Some((_, existing)) => *existing = block_entity,
+      None => self.block_entities.push((pos, block_entity)),
+    }
+  }
+
+  /// Returns all the entities in this structure, and their relative positions.
+  pub fn entities(&self) -> &[(Pos, Entity)] { &self.entities }
+
+  /// Adds an entity at the given relative position. Panics if the position is
+  /// outside the structure.
+  pub fn add_entity(&mut self, pos: Pos, entity: Entity) {
+    assert!(self.contains(pos), "position {pos:?} is out of bounds");
+
+    self.entities.push((pos, entity));
+  }
+
   /// Returns an iterator over all blocks in this structure.
   pub fn blocks(&self) -> BlocksIterExclusive {
     BlocksIterExclusive::new(
@@ -58,71 +142,141 @@ impl Structure {
   /// Returns the block in the structure at the given relative position. Returns
   /// `AIR` if the given position is not within the structure.
   pub fn get(&self, pos: Pos) -> BlockState {
-    if self.contains(pos) {
-      self.storage[(pos.y as u32 * self.depth * self.width
-        + pos.z as u32 * self.width
-        + pos.x as u32) as usize]
-    } else {
-      BlockState::AIR
-    }
+    if self.contains(pos) { self.storage[self.index(pos)] } else { BlockState::AIR }
   }
 
   /// Sets the block in the structure at the given relative position. Panics if
   /// the position is outside the structure.
   pub fn set(&mut self, pos: Pos, state: BlockState) {
     if self.contains(pos) {
-      self.storage[(pos.y as u32 * self.depth * self.width
-        + pos.z as u32 * self.width
-        + pos.x as u32) as usize] = state;
+      let index = self.index(pos);
+      self.storage[index] = state;
     } else {
       panic!("position {:?} is out of bounds", pos);
     }
   }
 
+  /// Returns how the block at the given relative position is placed. Returns
+  /// `Normal` if the given position is not within the structure.
+  pub fn placement(&self, pos: Pos) -> Placement {
+    if self.contains(pos) { self.placements[self.index(pos)] } else { Placement::Normal }
+  }
+
+  /// Sets how the block at the given relative position is placed. Panics if the
+  /// position is outside the structure.
+  pub fn set_placement(&mut self, pos: Pos, placement: Placement) {
+    assert!(self.contains(pos), "position {pos:?} is out of bounds");
+
+    let index = self.index(pos);
+    self.placements[index] = placement;
+  }
+
+  fn index(&self, pos: Pos) -> usize {
+    (pos.y as u32 * self.depth * self.width + pos.z as u32 * self.width + pos.x as u32) as usize
+  }
+
   /// Rotates the structure on the Y axist by the given delta, in multiples of
   /// 90 degrees. Positive delta means clockwise.
   pub fn rotate(&mut self, delta: i32) {
-    let delta = delta.rem_euclid(4);
-    if delta == 0 {
-      return;
+    for _ in 0..delta.rem_euclid(4) {
+      self.rotate_once();
     }
+  }
 
-    fn idx(structure: &Structure, x: u32, y: u32, z: u32) -> usize {
-      (y * structure.depth * structure.width + z * structure.width + x) as usize
+  /// Rotates the structure clockwise by 90 degrees. The block at `(x, z)` ends
+  /// up at `(depth - 1 - z, x)`, and the width and depth are swapped. Blocks
+  /// are turned to match, so a chest facing north ends up facing east.
+  fn rotate_once(&mut self) {
+    let mut rotated = Structure::new(self.depth, self.height, self.width);
+
+    for pos in self.blocks() {
+      rotated.set(self.rotate_pos(pos), rotate_block(self.get(pos), Direction::East));
+      rotated.set_placement(self.rotate_pos(pos), self.placement(pos));
     }
 
-    for y in 0..self.height {
-      for z in 0..self.depth / 2 {
-        for x in 0..=self.width / 2 {
-          let q1 = idx(self, x, y, z);
-          let q2 = idx(self, z, y, self.width - 1 - x);
-          let q3 = idx(self, self.width - 1 - x, y, self.depth - 1 - z);
-          let q4 = idx(self, self.depth - 1 - z, y, x);
-
-          match delta {
-            1 => {
-              self.storage.swap(q1, q4);
-              self.storage.swap(q3, q2);
-              self.storage.swap(q1, q3);
-            }
-            2 => {
-              self.storage.swap(q1, q3);
-              self.storage.swap(q2, q4);
-            }
-            3 => {
-              self.storage.swap(q1, q2);
-              self.storage.swap(q3, q4);
-              self.storage.swap(q1, q3);
-            }
-            _ => {}
-          }
-        }
-      }
+    for connector in &self.connectors {
+      rotated.connectors.push(Connector {
+        pos:    self.rotate_pos(connector.pos),
+        facing: connector.facing.right(),
+        tag:    connector.tag.clone(),
+      });
+    }
+
+    for (pos, block_entity) in &self.block_entities {
+      rotated.block_entities.push((self.rotate_pos(*pos), block_entity.clone()));
+    }
+    for (pos, entity) in &self.entities {
+      rotated.entities.push((self.rotate_pos(*pos), entity.clone()));
     }
 
-    if delta % 2 == 1 {
-      std::mem::swap(&mut self.width, &mut self.depth);
+    *self = rotated;
+  }
+
+  fn rotate_pos(&self, pos: Pos) -> Pos { Pos::new(self.depth as i32 - 1 - pos.z, pos.y, pos.x) }
+}
+
+/// Rotates the `axis` or `facing` of a block, so that a block that faced
+/// north faces `dir`. Blocks without a direction are returned as is.
+pub fn rotate_block(block: BlockState, dir: Direction) -> BlockState {
+  fn rotate_cw(block: BlockState) -> BlockState {
+    if let Some(PropValue::Enum(axis)) = block.prop("axis") {
+      let axis = match axis {
+        "x" => "z",
+        "z" => "x",
+        _ => return block,
+      };
+      return block.with_prop("axis", axis);
+    }
+    if let Some(PropValue::Enum(facing)) = block.prop("facing") {
+      let facing = match facing {
+        "north" => "east",
+        "east" => "south",
+        "south" => "west",
+        "west" => "north",
+        _ => return block,
+      };
+      return block.with_prop("facing", facing);
     }
+
+    let state = block.state.state().unwrap_or_default();
+
+    let new_state = match block.block {
+      // axis=x -> axis=z
+      block_kind![log] if state & 0b1100 == 0b0100 => state & 0b0011 | 0b1000,
+      // axis=z -> axis=x
+      block_kind![log] if state & 0b1100 == 0b1000 => state & 0b0011 | 0b0100,
+
+      // facing=north -> east -> south -> west
+      block_kind![chest] => match state {
+        2 => 5,
+        5 => 3,
+        3 => 4,
+        4 => 2,
+        _ => state,
+      },
+
+      // facing=east -> south -> west -> north, keeping the upside down bit.
+      kind if kind.name().ends_with("_stairs") => {
+        let facing = match state & 0b0011 {
+          0 => 2,
+          2 => 1,
+          1 => 3,
+          _ => 0,
+        };
+        state & 0b1100 | facing
+      }
+
+      _ => return block,
+    };
+
+    block.with_data(new_state)
+  }
+
+  match dir {
+    Direction::North => block,
+    Direction::East => rotate_cw(block),
+    Direction::South => rotate_cw(rotate_cw(block)),
+    Direction::West => rotate_cw(rotate_cw(rotate_cw(block))),
   }
 }
 
@@ -198,4 +352,21 @@ mod tests {
     structure.rotate(-1);
     assert_eq!(structure.storage, original);
   }
+
+  #[test]
+  fn rotate_blocks() {
+    let log = block![log[axis = "x", variant = "oak"]];
+    assert_eq!(rotate_block(log, Direction::East), block![log[axis = "z", variant = "oak"]]);
+    assert_eq!(rotate_block(log, Direction::South), log);
+
+    let chest = block![chest[facing = "north"]];
+    assert_eq!(rotate_block(chest, Direction::East), block![chest[facing = "east"]]);
+    assert_eq!(rotate_block(chest, Direction::West), block![chest[facing = "west"]]);
+
+    // Blocks with a data value are rotated too.
+    assert_eq!(rotate_block(block![log[4]], Direction::East), block![log[8]]);
+    assert_eq!(rotate_block(block![chest[2]], Direction::East), block![chest[5]]);
+    assert_eq!(rotate_block(block![oak_stairs[3]], Direction::East), block![oak_stairs[0]]);
+    assert_eq!(rotate_block(block![oak_stairs[4]], Direction::East), block![oak_stairs[6]]);
+  }
 }
@@ -0,0 +1,25 @@
+orientation horizontal
+
+S: minecraft:stonebrick
+A: connector hall
+B: connector hall minecraft:cobblestone
+
+layer start
+S A S
+S A S
+S B S
+==
+
+layer middle
+S S S
+S   S
+S S S
+==
+
+repeat middle
+
+layer end
+S S S
+S A S
+S S S
+==
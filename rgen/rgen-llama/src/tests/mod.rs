@@ -1,8 +1,12 @@
-use rgen_base::{Pos, block};
+use std::path::Path;
+
+use rgen_base::{BlockState, Direction, Pos, block};
+
+use crate::{Placement, check};
 
 #[test]
 fn parse_house() {
-  let house = crate::parse(include_str!("./house.ll"));
+  let house = crate::parse(include_str!("./house.ll")).unwrap();
 
   assert_eq!(house.get(Pos::new(0, 0, 0)), block![stone[0]]);
   assert_eq!(house.get(Pos::new(1, 0, 0)), block![stone[0]]);
@@ -12,8 +16,8 @@ fn parse_house() {
 
 #[test]
 fn parse_house_vertical() {
-  let house = crate::parse(include_str!("./house.ll"));
-  let vertical = crate::parse(include_str!("./house_vertical.ll"));
+  let house = crate::parse(include_str!("./house.ll")).unwrap();
+  let vertical = crate::parse(include_str!("./house_vertical.ll")).unwrap();
 
   assert_eq!(house.get(Pos::new(0, 0, 0)), block![stone[0]]);
   assert_eq!(house.get(Pos::new(1, 0, 0)), block![stone[0]]);
@@ -29,3 +33,142 @@ fn parse_house_vertical() {
     }
   }
 }
+
+#[test]
+fn parse_connectors() {
+  let corridor = crate::parse(include_str!("./corridor.ll")).unwrap();
+
+  let connectors = corridor.connectors();
+  assert_eq!(connectors.len(), 4);
+  assert!(connectors.iter().all(|c| c.tag == "hall"));
+  assert!(connectors.iter().filter(|c| c.facing == Direction::North).count() == 3);
+  assert!(connectors.iter().any(|c| c.pos == Pos::new(1, 1, 3) && c.facing == Direction::South));
+
+  // Connectors are air by default, but can have a block placed in them.
+  assert_eq!(corridor.get(Pos::new(1, 1, 0)), BlockState::AIR);
+  assert_eq!(corridor.get(Pos::new(1, 0, 0)), block![cobblestone[0]]);
+}
+
+#[test]
+fn rotate_connectors() {
+  let mut corridor = crate::parse(include_str!("./corridor.ll")).unwrap();
+  corridor.rotate(1);
+
+  assert_eq!(corridor.width(), 4);
+  assert_eq!(corridor.depth(), 3);
+  assert!(
+    corridor.connectors().iter().any(|c| c.pos == Pos::new(0, 1, 1) && c.facing == Direction::West)
+  );
+  assert!(
+    corridor.connectors().iter().any(|c| c.pos == Pos::new(3, 0, 1) && c.facing == Direction::East)
+  );
+}
+
+#[test]
+fn parse_block_entities() {
+  let mut storeroom = crate::parse(include_str!("./storeroom.ll")).unwrap();
+
+  assert_eq!(storeroom.get(Pos::new(0, 1, 1)), block![chest[3]]);
+  assert_eq!(storeroom.get(Pos::new(1, 1, 1)), BlockState::AIR);
+
+  let [(pos, chest)] = storeroom.block_entities() else { panic!() };
+  assert_eq!(*pos, Pos::new(0, 1, 1));
+  assert_eq!(chest.loot_table.as_deref(), Some("minecraft:chests/village_blacksmith"));
+
+  let [(pos, villager)] = storeroom.entities() else { panic!() };
+  assert_eq!(*pos, Pos::new(1, 1, 1));
+  assert_eq!(villager.id, "minecraft:villager");
+
+  // Block entities and entities move along with their blocks, and the chest
+  // turns from facing south to facing west.
+  storeroom.rotate(1);
+  assert_eq!(storeroom.block_entities()[0].0, Pos::new(0, 1, 0));
+  assert_eq!(storeroom.get(Pos::new(0, 1, 0)), block![chest[4]]);
+  assert_eq!(storeroom.entities()[0].0, Pos::new(0, 1, 1));
+}
+
+#[test]
+fn parse_property_maps() {
+  let pillar = crate::parse(include_str!("./pillar.ll")).unwrap();
+
+  assert_eq!(pillar.get(Pos::new(0, 0, 0)), block![log[variant = "spruce"]]);
+  assert_eq!(pillar.get(Pos::new(1, 2, 0)), block![log[axis = "x", variant = "spruce"]]);
+  assert_eq!(pillar.get(Pos::new(1, 0, 0)), block![oak_stairs[facing = "east", half = "top"]]);
+}
+
+#[test]
+fn unknown_property() {
+  let err = crate::parse("orientation horizontal\n\nX: minecraft:log[color=red]\n\nlayer\nX\n==\n")
+    .unwrap_err();
+
+  assert_eq!(err.message, "unknown property 'color' for block minecraft:log");
+  assert_eq!((err.line, err.column), (3, 18));
+  assert_eq!(
+    err.to_string(),
+    "unknown property 'color' for block minecraft:log at 3:18
+  |
+3 | X: minecraft:log[color=red]
+  |                  ^"
+  );
+}
+
+#[test]
+fn invalid_property_value() {
+  let err = crate::parse("orientation horizontal\n\nX: minecraft:log[axis=w]\n\nlayer\nX\n==\n")
+    .unwrap_err();
+
+  assert!(err.message.starts_with("invalid value 'w' for property 'axis'"));
+  assert_eq!((err.line, err.column), (3, 23));
+}
+
+#[test]
+fn parse_errors() {
+  let err = |input: &str| {
+    let err = crate::parse(input).unwrap_err();
+    (err.message, err.line, err.column)
+  };
+
+  assert_eq!(err("layer\nX\n==\n"), ("unknown block 'X'".into(), 2, 1));
+  assert_eq!(
+    err("orientation horizontal\nX: minecraft:stne\n"),
+    ("unknown block 'minecraft:stne'".into(), 2, 4)
+  );
+  assert_eq!(err("X: minecraft:stone\n"), ("missing orientation declaration".into(), 2, 1));
+  assert_eq!(
+    err("orientation horizontal\nH: connector hall\nlayer\n  \n  H\n  \n==\n"),
+    ("connector must be on exactly one side of the structure".into(), 5, 3)
+  );
+}
+
+/// The crates that include structures check them in their build scripts. This
+/// covers every structure in the workspace, including the ones in this crate.
+#[test]
+fn all_structures_parse() {
+  let files = check::find_structures(Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap());
+  assert!(files.len() > 10, "only found {} structures", files.len());
+
+  let errors = check::structure_errors(&files);
+  assert!(errors.is_empty(), "invalid structures:\n{}", errors.join("\n\n"));
+}
+
+#[test]
+fn parse_placements() {
+  let mut platform = crate::parse(include_str!("./platform.ll")).unwrap();
+
+  assert_eq!(platform.get(Pos::new(0, 0, 0)), block![cobblestone[0]]);
+  assert_eq!(platform.placement(Pos::new(0, 0, 0)), Placement::Foundation);
+  assert_eq!(platform.placement(Pos::new(1, 0, 0)), Placement::Normal);
+  assert_eq!(platform.get(Pos::new(0, 1, 0)), BlockState::AIR);
+  assert_eq!(platform.placement(Pos::new(0, 1, 0)), Placement::Force);
+  assert_eq!(platform.placement(Pos::new(0, 1, 1)), Placement::IfReplaceable);
+  assert_eq!(platform.placement(Pos::new(2, 1, 1)), Placement::Keep);
+
+  // Placements move along with their blocks.
+  platform.rotate(1);
+  assert_eq!(platform.placement(Pos::new(0, 1, 0)), Placement::IfReplaceable);
+  assert_eq!(platform.placement(Pos::new(1, 1, 2)), Placement::Keep);
+
+  let err =
+    crate::parse("orientation horizontal\nX: minecraft:stone force foundation\n").unwrap_err();
+  assert_eq!((err.message.as_str(), err.line, err.column), ("duplicate placement rule", 2, 26));
+}
@@ -0,0 +1,11 @@
+orientation horizontal
+
+X: minecraft:log[axis=x,variant=spruce]
+Y: minecraft:log[variant=spruce]
+S: minecraft:oak_stairs[facing=east,half=top]
+
+layer
+X X X
+Y   Y
+Y S Y
+==
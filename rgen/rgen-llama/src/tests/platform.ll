@@ -0,0 +1,17 @@
+orientation horizontal
+
+F: minecraft:cobblestone foundation
+P: minecraft:planks
+A: minecraft:air force
+G: minecraft:tallgrass[1] if_replaceable
+K: keep
+
+layer front
+A A K
+F P F
+==
+
+layer back
+G A K
+F P F
+==
@@ -0,0 +1,14 @@
+orientation horizontal
+
+_: minecraft:planks
+C: minecraft:chest[3] loot minecraft:chests/village_blacksmith
+V: entity minecraft:villager
+
+layer back
+_ _ _
+==
+
+layer middle
+C V
+_ _ _
+==
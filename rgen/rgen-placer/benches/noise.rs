@@ -2,7 +2,9 @@
 
 extern crate test;
 
-use rgen_placer::noise::{NoiseGenerator, OctavedNoise, OpenSimplexNoise, PerlinNoise};
+use rgen_placer::noise::{
+  NoiseGenerator, NoiseGenerator3D, OctavedNoise, OpenSimplexNoise, PerlinNoise,
+};
 use test::Bencher;
 
 #[bench]
@@ -22,3 +24,95 @@ fn bench_open_simplex(b: &mut Bencher) {
     test::black_box(noise.generate(0.0, 0.0));
   });
 }
+
+#[bench]
+fn bench_perlin_3d_column(b: &mut Bencher) {
+  let noise: OctavedNoise<PerlinNoise, 5> = OctavedNoise::new(0, 1.0 / 64.0);
+
+  b.iter(|| {
+    for y in 0..256 {
+      test::black_box(noise.generate_3d(0.0, y as f64, 0.0));
+    }
+  });
+}
+
+#[bench]
+fn bench_perlin_3d_column_batch(b: &mut Bencher) {
+  let noise: OctavedNoise<PerlinNoise, 5> = OctavedNoise::new(0, 1.0 / 64.0);
+  let mut column = [0.0; 256];
+
+  b.iter(|| {
+    noise.generate_column_3d(0.0, 0.0, 0.0, 1.0, &mut column);
+    test::black_box(&column);
+  });
+}
+
+#[bench]
+fn bench_perlin_grid(b: &mut Bencher) {
+  let noise: OctavedNoise<PerlinNoise, 8> = OctavedNoise::new(0, 1.0 / 64.0);
+
+  b.iter(|| {
+    for x in 0..16 {
+      for y in 0..16 {
+        test::black_box(noise.generate(x as f64, y as f64));
+      }
+    }
+  });
+}
+
+#[bench]
+fn bench_perlin_grid_batch(b: &mut Bencher) {
+  let noise: OctavedNoise<PerlinNoise, 8> = OctavedNoise::new(0, 1.0 / 64.0);
+  let mut grid = [[0.0; 16]; 16];
+
+  b.iter(|| {
+    noise.generate_grid(0.0, 0.0, 1.0, &mut grid);
+    test::black_box(&grid);
+  });
+}
+
+#[bench]
+fn bench_open_simplex_3d_column(b: &mut Bencher) {
+  let noise: OctavedNoise<OpenSimplexNoise, 5> = OctavedNoise::new(0, 1.0 / 64.0);
+
+  b.iter(|| {
+    for y in 0..256 {
+      test::black_box(noise.generate_3d(0.0, y as f64, 0.0));
+    }
+  });
+}
+
+#[bench]
+fn bench_open_simplex_3d_column_batch(b: &mut Bencher) {
+  let noise: OctavedNoise<OpenSimplexNoise, 5> = OctavedNoise::new(0, 1.0 / 64.0);
+  let mut column = [0.0; 256];
+
+  b.iter(|| {
+    noise.generate_column_3d(0.0, 0.0, 0.0, 1.0, &mut column);
+    test::black_box(&column);
+  });
+}
+
+#[bench]
+fn bench_open_simplex_grid(b: &mut Bencher) {
+  let noise: OctavedNoise<OpenSimplexNoise, 8> = OctavedNoise::new(0, 1.0 / 64.0);
+
+  b.iter(|| {
+    for x in 0..16 {
+      for y in 0..16 {
+        test::black_box(noise.generate(x as f64, y as f64));
+      }
+    }
+  });
+}
+
+#[bench]
+fn bench_open_simplex_grid_batch(b: &mut Bencher) {
+  let noise: OctavedNoise<OpenSimplexNoise, 8> = OctavedNoise::new(0, 1.0 / 64.0);
+  let mut grid = [[0.0; 16]; 16];
+
+  b.iter(|| {
+    noise.generate_grid(0.0, 0.0, 1.0, &mut grid);
+    test::black_box(&grid);
+  });
+}
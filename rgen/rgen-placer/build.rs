@@ -0,0 +1,3 @@
+// Structures are parsed at runtime, so check them here to turn a typo into a
+// build error instead of a crash.
+fn main() { rgen_llama::check_structures("src"); }
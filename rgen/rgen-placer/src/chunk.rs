@@ -3,9 +3,10 @@
 // in this chunk are temporary, and unique to each chunk. So biome id 0 in one
 // chunk is not the same as biome id 0 in another chunk.
 //
-// Each biome cached chunk can only store one of 16 different biomes per block,
-// as there will not be more than 16 biomes in one chunk. This means that only 4
-// bits of extra information are needed for each block.
+// IDs are a `TemporaryBiome(u16)`, handed out as each new biome is seen, and
+// there is no cap on how many biomes a chunk can hold. A chunk only has 256
+// columns, each with a surface biome and a handful of cave biomes, so the IDs
+// stay far below `u16::MAX`.
 
 use rgen_base::{BlockInfo, BlockState, Chunk, ChunkRelPos};
 use rgen_world::BlockInfoSupplier;
@@ -23,14 +24,92 @@ pub struct BiomeCachedChunk<'a> {
 #[derive(Clone, Copy)]
 pub struct BiomeColumn {
   pub surface: TemporaryBiome,
-  pub cave:    TemporaryBiome,
+  pub caves:   CaveSpans,
 
   pub min_height: i32,
+
   // max_height: i32,
+  /// How far this column is into its surface biome. See
+  /// [`BiomeCachedChunk::blend_weight`].
+  pub blend_weight: f32,
+
+  /// The underground fluid in this column. See
+  /// [`BiomeCachedChunk::fluid_level`].
+  pub fluid: Option<FluidLevel>,
 }
 
+/// The level of an underground body of fluid. Carved out blocks at or below
+/// `level` are filled with `fluid`.
 #[derive(Clone, Copy, PartialEq, Eq)]
-pub struct TemporaryBiome(pub u8);
+pub struct FluidLevel {
+  pub level: i32,
+  pub fluid: BlockState,
+}
+
+impl FluidLevel {
+  /// Returns true if the given Y level is below the surface of this fluid.
+  pub fn contains(&self, y: i32) -> bool { y <= self.level }
+}
+
+/// The maximum number of cave biomes stacked on top of each other in a single
+/// column.
+pub const MAX_CAVE_SPANS: usize = 4;
+
+/// The cave biomes in a column, from the bottom of the world up to the surface.
+/// Each span covers every Y level up to the top of that span, and the last
+/// span continues up to the surface.
+#[derive(Clone, Copy)]
+pub struct CaveSpans {
+  /// The top (exclusive) of each span, along with its biome.
+  spans: [(i32, TemporaryBiome); MAX_CAVE_SPANS],
+  len:   u8,
+}
+
+impl CaveSpans {
+  /// Creates a column with a single cave biome.
+  pub const fn new(biome: TemporaryBiome) -> Self {
+    CaveSpans { spans: [(i32::MAX, biome); MAX_CAVE_SPANS], len: 1 }
+  }
+
+  /// Adds a biome on top of all the existing spans, starting at `min_y`. If
+  /// this biome matches the top span, the top span is extended instead.
+  ///
+  /// Once there are [`MAX_CAVE_SPANS`] spans, the biome is dropped and the top
+  /// span is extended, and this returns `false`.
+  pub fn push(&mut self, min_y: i32, biome: TemporaryBiome) -> bool {
+    let top = self.len as usize - 1;
+    if self.spans[top].1 == biome {
+      return true;
+    }
+    if self.is_full() {
+      return false;
+    }
+
+    self.spans[top].0 = min_y;
+    self.spans[top + 1] = (i32::MAX, biome);
+    self.len += 1;
+    true
+  }
+
+  /// Returns `true` if no more spans can be added.
+  pub fn is_full(&self) -> bool { self.len as usize == MAX_CAVE_SPANS }
+
+  /// Returns the cave biome at the given Y level.
+  pub fn get(&self, y: i32) -> TemporaryBiome {
+    // The last span always has a top of `i32::MAX`, so this will always find a
+    // span.
+    for &(top, biome) in &self.spans[..self.len as usize] {
+      if y < top {
+        return biome;
+      }
+    }
+
+    unreachable!()
+  }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct TemporaryBiome(pub u16);
 
 impl TemporaryBiome {
   pub fn incr(&mut self) { self.0 += 1; }
@@ -38,10 +117,12 @@ impl TemporaryBiome {
 
 impl BiomeColumn {
   pub const ZERO: Self = BiomeColumn {
-    surface:    TemporaryBiome(0),
-    cave:       TemporaryBiome(0),
-    min_height: 0,
+    surface:      TemporaryBiome(0),
+    caves:        CaveSpans::new(TemporaryBiome(0)),
+    min_height:   0,
     // max_height: 0,
+    blend_weight: 1.0,
+    fluid:        None,
   };
 }
 
@@ -59,14 +140,28 @@ impl<'a> BiomeCachedChunk<'a> {
   pub fn set_active(&mut self, active: TemporaryBiome) { self.active = active; }
 
   pub fn is_active(&self, pos: ChunkRelPos) -> bool {
-    let column = self.biomes[pos.z() as usize][pos.x() as usize];
+    let column = &self.biomes[pos.z() as usize][pos.x() as usize];
     if pos.y() < column.min_height {
-      self.active == column.cave
+      self.active == column.caves.get(pos.y())
     } else {
       self.active == column.surface
     }
   }
 
+  /// Returns how far the given column is into its surface biome. This is 1.0
+  /// deep inside a biome, and drops towards 0.0 near biome edges. Placers can
+  /// use this to thin out towards the edge of a biome.
+  pub fn blend_weight(&self, pos: ChunkRelPos) -> f32 {
+    self.biomes[pos.z() as usize][pos.x() as usize].blend_weight
+  }
+
+  /// Returns the underground fluid level (the aquifer) of the given column, if
+  /// there is one. Caves in this column are filled with the fluid up to this
+  /// level, so cave placers can use this to avoid placing things underwater.
+  pub fn fluid_level(&self, pos: ChunkRelPos) -> Option<FluidLevel> {
+    self.biomes[pos.z() as usize][pos.x() as usize].fluid
+  }
+
   /// Sets the biome at the given position.
   ///
   /// This will not overwrite the previous biome, so it should not be called by
@@ -83,3 +178,30 @@ impl<'a> BiomeCachedChunk<'a> {
     self.chunk.set(pos, self.info.encode(state.into()))
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn cave_spans() {
+    let mut spans = CaveSpans::new(TemporaryBiome(0));
+    assert!(spans.push(16, TemporaryBiome(1)));
+    assert!(spans.push(32, TemporaryBiome(1)));
+    assert!(spans.push(48, TemporaryBiome(2)));
+
+    assert!(spans.get(0) == TemporaryBiome(0));
+    assert!(spans.get(15) == TemporaryBiome(0));
+    assert!(spans.get(16) == TemporaryBiome(1));
+    assert!(spans.get(47) == TemporaryBiome(1));
+    assert!(spans.get(48) == TemporaryBiome(2));
+    assert!(spans.get(200) == TemporaryBiome(2));
+
+    // Once the spans are full, the top span is extended.
+    assert!(spans.push(64, TemporaryBiome(3)));
+    assert!(spans.is_full());
+    assert!(!spans.push(80, TemporaryBiome(4)));
+    assert!(spans.get(70) == TemporaryBiome(3));
+    assert!(spans.get(90) == TemporaryBiome(3));
+  }
+}
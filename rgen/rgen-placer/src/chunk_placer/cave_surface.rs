@@ -0,0 +1,46 @@
+use rgen_base::{BlockFilter, BlockState, ChunkRelPos};
+
+use crate::{BiomeCachedChunk, ChunkPlacer, Random};
+
+/// Covers the floors and ceilings of caves with other blocks.
+pub struct CaveSurface {
+  /// The blocks that make up the cave walls.
+  pub replace: BlockFilter,
+  pub floor:   BlockState,
+  pub ceiling: BlockState,
+
+  /// The chance that any single block is replaced, from 0 to 1.
+  pub chance: f64,
+}
+
+impl ChunkPlacer for CaveSurface {
+  fn place(
+    &self,
+    chunk: &mut BiomeCachedChunk,
+    rng: &mut crate::Rng,
+    _chunk_pos: rgen_base::ChunkPos,
+  ) {
+    for x in 0..16 {
+      for z in 0..16 {
+        for y in 1..255 {
+          let pos = ChunkRelPos::new(x, y, z);
+          if !chunk.is_active(pos) || !self.replace.contains(chunk.get(pos)) {
+            continue;
+          }
+
+          let block = if chunk.get(pos.with_y(y + 1)) == block![air] {
+            self.floor
+          } else if chunk.get(pos.with_y(y - 1)) == block![air] {
+            self.ceiling
+          } else {
+            continue;
+          };
+
+          if rng.range(0.0..1.0) < self.chance {
+            chunk.set(pos, block);
+          }
+        }
+      }
+    }
+  }
+}
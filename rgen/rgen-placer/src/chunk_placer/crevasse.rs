@@ -16,12 +16,12 @@ pub struct Crevasse {
 }
 
 impl Crevasse {
-  pub fn new() -> Self {
+  pub fn new(seed: u64) -> Self {
     Crevasse {
       replace:    block![packed_ice].into(),
       height:     10,
       packed_ice: block![packed_ice],
-      noise:      OctavedNoise::new(0, 1.0 / 16.0),
+      noise:      OctavedNoise::new(seed, 1.0 / 16.0),
     }
   }
 }
@@ -1,16 +1,20 @@
+mod cave_surface;
 mod checkerboard;
 mod crevasse;
 mod lush_cave;
 mod ore;
 mod snow_snow;
 mod snow_stone;
+mod stalactite;
 
+pub use cave_surface::CaveSurface;
 pub use checkerboard::CheckerboardSurface;
 pub use crevasse::*;
 pub use lush_cave::LushCaveMoss;
 pub use ore::Ore;
 pub use snow_snow::SnowOnSnowSurface;
 pub use snow_stone::SnowOnStoneSurface;
+pub use stalactite::Stalactites;
 
 use rgen_base::{BlockState, ChunkRelPos, Pos};
 
@@ -1,9 +1,13 @@
 use rgen_base::{BlockState, Pos};
 use std::ops::RangeInclusive;
 
-use crate::{ChunkPlacer, Random, Rng, grid::PointGrid};
+use crate::{ChunkPlacer, Random, Rng, derive_pos_seed, derive_seed, grid::PointGrid};
 
 pub struct Ore {
+  /// The seed used to place veins. This must be the same for every chunk, so
+  /// that veins line up across chunk borders.
+  pub seed: u64,
+
   pub ore:           BlockState,
   pub avg_per_chunk: f64,
 
@@ -16,7 +20,7 @@ impl ChunkPlacer for Ore {
   fn place(
     &self,
     chunk: &mut crate::BiomeCachedChunk,
-    rng: &mut crate::Rng,
+    _rng: &mut crate::Rng,
     chunk_pos: rgen_base::ChunkPos,
   ) {
     let radius = self.size.end();
@@ -28,16 +32,15 @@ impl ChunkPlacer for Ore {
     let ore_max_x = ((min_pos.x + 16 + radius) as f64) / scale;
     let ore_max_z = ((min_pos.z + 16 + radius) as f64) / scale;
 
-    // FIXME: This is always given the same random number generator, so we can pick
-    // a seed off it by just grabbing the next number. This can't be relied on
-    // though! Need to rework the `ChunkPlacer` api a bit.
-    let seed = rng.next();
+    let seed = self.seed;
     let points = PointGrid.points_in_area(seed, ore_min_x, ore_min_z, ore_max_x, ore_max_z);
 
     for point in points {
+      // Each grid square has one point, so seed each vein from its square.
+      let square = Pos::new(point.0.floor() as i32, 0, point.1.floor() as i32);
+      let mut rng = Rng::new(derive_pos_seed(derive_seed(seed, "vein"), square));
+
       let mut pos = (point.0 * scale, 0.0, point.1 * scale);
-      let vein_seed = seed ^ (((pos.0 * 2048.0) as u64) << 8) ^ (((pos.2 * 2048.0) as u64) << 16);
-      let mut rng = Rng::new(vein_seed);
 
       pos.1 = rng.range(*self.height.start()..=*self.height.end()) as f64;
 
@@ -1,7 +1,7 @@
 use rgen_base::{BlockFilter, BlockState, ChunkRelPos};
 
 use crate::{
-  BiomeCachedChunk, ChunkPlacer, Random,
+  BiomeCachedChunk, ChunkPlacer, Random, derive_seed,
   noise::{NoiseGenerator, OpenSimplexNoise, SeededNoise},
 };
 
@@ -17,7 +17,7 @@ pub struct SnowOnSnowSurface {
 impl SnowOnSnowSurface {
   pub fn new(seed: u64) -> Self {
     SnowOnSnowSurface {
-      noise:       OpenSimplexNoise::new(seed),
+      noise:       OpenSimplexNoise::new(derive_seed(seed, "snow on snow")),
       a:           block![snow_layer],
       place_above: block![snow_layer].into(),
       min_snow:    3,
@@ -1,7 +1,7 @@
 use rgen_base::{BlockFilter, BlockState, ChunkRelPos};
 
 use crate::{
-  BiomeCachedChunk, ChunkPlacer, Random,
+  BiomeCachedChunk, ChunkPlacer, Random, derive_seed,
   noise::{NoiseGenerator, OpenSimplexNoise, SeededNoise},
 };
 
@@ -16,7 +16,7 @@ pub struct SnowOnStoneSurface {
 impl SnowOnStoneSurface {
   pub fn new(seed: u64) -> Self {
     SnowOnStoneSurface {
-      noise:       OpenSimplexNoise::new(seed),
+      noise:       OpenSimplexNoise::new(derive_seed(seed, "snow on stone")),
       a:           block![snow_layer],
       place_above: block![stone].into(),
       min_snow:    1,
@@ -0,0 +1,59 @@
+use rgen_base::{BlockFilter, BlockState, ChunkRelPos};
+
+use crate::{BiomeCachedChunk, ChunkPlacer, Random};
+
+/// Hangs pillars from the ceilings of caves, and grows them up from the floor.
+pub struct Stalactites {
+  /// The blocks that pillars can grow from.
+  pub ground:     BlockFilter,
+  pub block:      BlockState,
+  pub max_length: i32,
+
+  /// The chance that a pillar will grow from any single ceiling or floor
+  /// block, from 0 to 1.
+  pub chance: f64,
+}
+
+impl ChunkPlacer for Stalactites {
+  fn place(
+    &self,
+    chunk: &mut BiomeCachedChunk,
+    rng: &mut crate::Rng,
+    _chunk_pos: rgen_base::ChunkPos,
+  ) {
+    for x in 0..16 {
+      for z in 0..16 {
+        for y in 1..255 {
+          let pos = ChunkRelPos::new(x, y, z);
+          if !chunk.is_active(pos) || chunk.get(pos) != block![air] {
+            continue;
+          }
+
+          // Stalactites grow down from the ceiling, and stalagmites grow up from the
+          // floor.
+          let dir = if self.ground.contains(chunk.get(pos.with_y(y + 1))) {
+            -1
+          } else if self.ground.contains(chunk.get(pos.with_y(y - 1))) {
+            1
+          } else {
+            continue;
+          };
+
+          if rng.range(0.0..1.0) >= self.chance {
+            continue;
+          }
+
+          let length = rng.range(1..=self.max_length);
+          for i in 0..length {
+            let pos = pos.with_y(y + i * dir);
+            if !(0..256).contains(&pos.y()) || chunk.get(pos) != block![air] {
+              break;
+            }
+
+            chunk.set(pos, self.block);
+          }
+        }
+      }
+    }
+  }
+}
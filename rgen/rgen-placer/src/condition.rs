@@ -0,0 +1,283 @@
+//! Placement conditions, which can be attached to any placer with
+//! [`Placer::when`].
+//!
+//! These are checked at the position passed to the placer, before the placer
+//! runs. If the condition fails, the placer is skipped.
+
+use std::ops::RangeInclusive;
+
+use rgen_base::{BlockFilter, Pos, WORLD_HEIGHT};
+use rgen_world::PartialWorld;
+
+use crate::{Placer, Result, Rng, grid::PointDistribution, noise::NoiseGenerator};
+
+/// A condition that must pass for a placer to be run at a given position.
+pub trait Condition: Send + Sync {
+  fn test(&self, world: &PartialWorld, pos: Pos) -> bool;
+
+  /// Passes if both `self` and `other` pass.
+  fn and<C: Condition>(self, other: C) -> And<Self, C>
+  where
+    Self: Sized,
+  {
+    And(self, other)
+  }
+
+  /// Passes if either `self` or `other` pass.
+  fn or<C: Condition>(self, other: C) -> Or<Self, C>
+  where
+    Self: Sized,
+  {
+    Or(self, other)
+  }
+
+  /// Passes if `self` fails.
+  fn not(self) -> Not<Self>
+  where
+    Self: Sized,
+  {
+    Not(self)
+  }
+}
+
+/// A placer that only runs when the given condition passes. Created with
+/// [`Placer::when`].
+pub struct Conditional<P, C> {
+  pub placer:    P,
+  pub condition: C,
+}
+
+impl<P: Placer, C: Condition> Placer for Conditional<P, C> {
+  fn radius(&self) -> u8 { self.placer.radius() }
+
+  fn avg_per_chunk(&self) -> f64 { self.placer.avg_per_chunk() }
+
+  fn distribution(&self) -> Box<dyn PointDistribution> { self.placer.distribution() }
+
+  fn place(&self, world: &mut PartialWorld, rng: &mut Rng, pos: Pos) -> Result {
+    if self.condition.test(world, pos) { self.placer.place(world, rng, pos) } else { Ok(()) }
+  }
+}
+
+pub struct And<A, B>(pub A, pub B);
+pub struct Or<A, B>(pub A, pub B);
+pub struct Not<A>(pub A);
+
+impl<A: Condition, B: Condition> Condition for And<A, B> {
+  fn test(&self, world: &PartialWorld, pos: Pos) -> bool {
+    self.0.test(world, pos) && self.1.test(world, pos)
+  }
+}
+
+impl<A: Condition, B: Condition> Condition for Or<A, B> {
+  fn test(&self, world: &PartialWorld, pos: Pos) -> bool {
+    self.0.test(world, pos) || self.1.test(world, pos)
+  }
+}
+
+impl<A: Condition> Condition for Not<A> {
+  fn test(&self, world: &PartialWorld, pos: Pos) -> bool { !self.0.test(world, pos) }
+}
+
+/// Passes if the block below the position is in the given filter.
+pub struct Ground(pub BlockFilter);
+
+impl Condition for Ground {
+  fn test(&self, world: &PartialWorld, pos: Pos) -> bool {
+    self.0.contains(world.get(pos + Pos::new(0, -1, 0)))
+  }
+}
+
+/// Passes if the Y level of the position is in the given range.
+pub struct Height(pub RangeInclusive<i32>);
+
+impl Condition for Height {
+  fn test(&self, _: &PartialWorld, pos: Pos) -> bool { self.0.contains(&pos.y) }
+}
+
+/// Passes if the ground around the position is flat enough. The ground height
+/// is sampled `distance` blocks away on each axis, and each sample must be
+/// within `max_slope` blocks of the ground at the position.
+pub struct Slope {
+  pub max_slope: i32,
+  pub distance:  i32,
+
+  /// The blocks that are not considered ground (usually air and plants).
+  pub passable: BlockFilter,
+}
+
+impl Default for Slope {
+  fn default() -> Self {
+    Slope {
+      max_slope: 2,
+      distance:  2,
+      passable:  [block![air], block![tallgrass], block![snow_layer]].into(),
+    }
+  }
+}
+
+impl Condition for Slope {
+  fn test(&self, world: &PartialWorld, pos: Pos) -> bool {
+    for offset in [
+      Pos::new(self.distance, 0, 0),
+      Pos::new(-self.distance, 0, 0),
+      Pos::new(0, 0, self.distance),
+      Pos::new(0, 0, -self.distance),
+    ] {
+      let column = pos + offset;
+
+      // Search downwards for the ground, only within the allowed slope. The
+      // position passed to placers is the block above the ground, so the ground
+      // should be one block below `pos.y`.
+      let ground = (pos.y - 1 - self.max_slope..=pos.y - 1 + self.max_slope)
+        .rev()
+        .find(|&y| !self.passable.contains(world.get(column.with_y(y))));
+
+      match ground {
+        // The top of the range might be inside a wall, which is too steep.
+        Some(y) if y == pos.y - 1 + self.max_slope => {
+          if !self.passable.contains(world.get(column.with_y(y + 1))) {
+            return false;
+          }
+        }
+        Some(_) => {}
+        None => return false,
+      }
+    }
+
+    true
+  }
+}
+
+/// Passes if any of the given blocks are within `radius` blocks of the
+/// position. Combine with [`Condition::not`] to require placers to be far away
+/// from something.
+///
+/// ```
+/// # use rgen_base::block;
+/// # use rgen_placer::condition::{Condition, Near};
+/// // Near water.
+/// let near_water = Near { blocks: block![water].into(), radius: 4 };
+///
+/// // Not near lava.
+/// let not_near_lava = Near { blocks: block![lava].into(), radius: 8 }.not();
+/// ```
+pub struct Near {
+  pub blocks: BlockFilter,
+  pub radius: i32,
+}
+
+impl Condition for Near {
+  fn test(&self, world: &PartialWorld, pos: Pos) -> bool {
+    let radius_squared = self.radius * self.radius;
+
+    for y in -self.radius..=self.radius {
+      for z in -self.radius..=self.radius {
+        for x in -self.radius..=self.radius {
+          if x * x + y * y + z * z > radius_squared {
+            continue;
+          }
+
+          if self.blocks.contains(world.get(pos + Pos::new(x, y, z))) {
+            return true;
+          }
+        }
+      }
+    }
+
+    false
+  }
+}
+
+/// Passes if there is nothing but passable blocks between the position and the
+/// top of the world.
+pub struct SkyAccess {
+  /// The blocks that light can pass through.
+  pub passable: BlockFilter,
+}
+
+impl Default for SkyAccess {
+  fn default() -> Self { SkyAccess { passable: block![air].into() } }
+}
+
+impl Condition for SkyAccess {
+  fn test(&self, world: &PartialWorld, pos: Pos) -> bool {
+    (pos.y..WORLD_HEIGHT).all(|y| self.passable.contains(world.get(pos.with_y(y))))
+  }
+}
+
+/// Passes if the given noise map is above `threshold` at the position. The
+/// noise is sampled at `pos * scale`.
+pub struct NoiseThreshold<N> {
+  pub noise:     N,
+  pub scale:     f64,
+  pub threshold: f64,
+}
+
+impl<N: NoiseGenerator<Output = f64> + Send + Sync> Condition for NoiseThreshold<N> {
+  fn test(&self, _: &PartialWorld, pos: Pos) -> bool {
+    self.noise.generate(pos.x as f64 * self.scale, pos.z as f64 * self.scale) > self.threshold
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use rgen_base::StateId;
+  use rgen_world::{Context, PartialWorldStorage};
+
+  use super::*;
+
+  #[derive(Default)]
+  struct TestStorage {
+    blocks: HashMap<(i32, i32, i32), StateId>,
+  }
+
+  impl PartialWorldStorage for TestStorage {
+    fn get(&self, pos: Pos) -> StateId {
+      self.blocks.get(&(pos.x, pos.y, pos.z)).copied().unwrap_or(StateId::AIR)
+    }
+    fn set(&mut self, pos: Pos, block: StateId) {
+      self.blocks.insert((pos.x, pos.y, pos.z), block);
+    }
+    fn surfaces(&self, _: Pos) -> &[u8] { &[] }
+  }
+
+  #[test]
+  fn conditions_combine() {
+    let ctx = Context::new_test(0);
+    let mut world = PartialWorld::new(&ctx.blocks, TestStorage::default());
+
+    // A flat floor of grass at Y=63, with a block of stone sticking out.
+    for x in -4..=4 {
+      for z in -4..=4 {
+        world.set(Pos::new(x, 63, z), block![grass]);
+      }
+    }
+    world.set(Pos::new(2, 64, 0), block![stone]);
+    world.set(Pos::new(2, 65, 0), block![stone]);
+    world.set(Pos::new(2, 66, 0), block![stone]);
+
+    let pos = Pos::new(0, 64, 0);
+
+    assert!(Ground(block![grass].into()).test(&world, pos));
+    assert!(!Ground(block![stone].into()).test(&world, pos));
+    assert!(Height(60..=70).test(&world, pos));
+    assert!(!Height(70..=80).test(&world, pos));
+
+    assert!(!Slope::default().test(&world, pos));
+    assert!(Slope::default().test(&world, Pos::new(-2, 64, 0)));
+
+    assert!(Near { blocks: block![stone].into(), radius: 2 }.test(&world, pos));
+    assert!(!Near { blocks: block![stone].into(), radius: 1 }.test(&world, pos));
+
+    assert!(SkyAccess::default().test(&world, pos));
+    assert!(!SkyAccess::default().test(&world, Pos::new(0, 63, 0)));
+
+    assert!(Height(60..=70).and(Ground(block![grass].into())).test(&world, pos));
+    assert!(!Height(70..=80).and(Ground(block![grass].into())).test(&world, pos));
+    assert!(Height(70..=80).or(Ground(block![grass].into())).test(&world, pos));
+    assert!(!Ground(block![grass].into()).not().test(&world, pos));
+  }
+}
@@ -0,0 +1,100 @@
+use std::ops::RangeInclusive;
+
+use rgen_base::Pos;
+
+use super::{PointDistribution, PointGrid};
+use crate::{Random, Rng, derive_pos_seed};
+
+/// A clustered distribution. Each point of a [`PointGrid`] is the center of a
+/// cluster, and each cluster has a random number of points within `radius` of
+/// the center.
+///
+/// Note that there is one cluster per unit square, so there will be more than
+/// one point per square on average if clusters have more than one point.
+pub struct Clustered {
+  /// The number of points in each cluster.
+  pub points: RangeInclusive<u32>,
+  /// The maximum distance from the center of a cluster to each of its points.
+  pub radius: f64,
+}
+
+impl Clustered {
+  pub fn points_in_area(
+    &self,
+    seed: u64,
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+  ) -> impl Iterator<Item = (f64, f64)> + '_ {
+    // Clusters centered outside of the area can still have points inside of it.
+    let r = self.radius;
+    PointGrid.points_in_area(seed, min_x - r, min_y - r, max_x + r, max_y + r).flat_map(
+      move |center| {
+        let mut rng = Rng::new(derive_pos_seed(
+          seed,
+          Pos::new(center.0.floor() as i32, 0, center.1.floor() as i32),
+        ));
+        let count = rng.range(*self.points.start() as i32..=*self.points.end() as i32);
+
+        (0..count).filter_map(move |_| {
+          // Use the square root of the distance, so that points are spread evenly
+          // over the circle, instead of bunching up in the middle.
+          let angle = rng.range(0.0..std::f64::consts::TAU);
+          let dist = rng.range(0.0..1.0_f64).sqrt() * self.radius;
+
+          let p = (center.0 + angle.cos() * dist, center.1 + angle.sin() * dist);
+          if p.0 >= min_x && p.0 <= max_x && p.1 >= min_y && p.1 <= max_y { Some(p) } else { None }
+        })
+      },
+    )
+  }
+}
+
+impl PointDistribution for Clustered {
+  fn points_per_square(&self) -> f64 { f64::from(self.points.start() + self.points.end()) / 2.0 }
+
+  fn points_in_area(
+    &self,
+    seed: u64,
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+  ) -> Box<dyn Iterator<Item = (f64, f64)> + '_> {
+    Box::new(Clustered::points_in_area(self, seed, min_x, min_y, max_x, max_y))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn points_are_clustered() {
+    const SEED: u64 = 1234;
+    let clustered = Clustered { points: 2..=6, radius: 0.2 };
+
+    let points: Vec<_> = clustered.points_in_area(SEED, 0.0, 0.0, 32.0, 32.0).collect();
+
+    // Every point is close to the center of its cluster.
+    let centers: Vec<_> = PointGrid.points_in_area(SEED, -1.0, -1.0, 33.0, 33.0).collect();
+    for p in &points {
+      assert!(
+        centers.iter().any(|c| (p.0 - c.0).powi(2) + (p.1 - c.1).powi(2) <= 0.2_f64.powi(2)),
+        "{p:?} is not in a cluster"
+      );
+    }
+
+    let density = points.len() as f64 / (32.0 * 32.0);
+    assert!((density - clustered.points_per_square()).abs() < 0.5, "density = {density}");
+
+    // Smaller areas return the same points, including points from clusters
+    // centered outside of the area.
+    let small: Vec<_> = clustered.points_in_area(SEED, 4.0, 4.0, 8.0, 8.0).collect();
+    assert!(!small.is_empty());
+    for p in small {
+      assert!(points.contains(&p));
+    }
+  }
+}
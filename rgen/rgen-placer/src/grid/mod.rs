@@ -1,6 +1,43 @@
+use rgen_base::ChunkPos;
+
 use crate::{Random, Rng};
 
+mod cluster;
+mod poisson;
+mod weighted;
+
+pub use cluster::Clustered;
+pub use poisson::PoissonDisk;
+pub use weighted::Weighted;
+
+/// A deterministic way of spreading points out over an infinite plane.
+///
+/// Distributions don't depend on the order areas are requested in, so the same
+/// points are returned along the borders of neighboring areas.
+///
+/// Any distribution can be thinned out by a density map with [`Weighted`].
+pub trait PointDistribution: Send + Sync {
+  /// The average number of points in each unit square. This is used to scale
+  /// the distribution, so that placers end up with the right number of points
+  /// per chunk.
+  fn points_per_square(&self) -> f64 { 1.0 }
+
+  /// Returns all the points in the given area.
+  fn points_in_area(
+    &self,
+    seed: u64,
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+  ) -> Box<dyn Iterator<Item = (f64, f64)> + '_>;
+}
+
 /// A determinstic, randomly spread out grid of points.
+///
+/// There is exactly one point in each unit square, so points in neighboring
+/// squares can end up right next to each other. See [`PoissonDisk`] for a
+/// distribution with a minimum distance between points.
 pub struct PointGrid;
 
 impl Default for PointGrid {
@@ -46,8 +83,27 @@ impl PointGrid {
     })
   }
 
+  /// Returns all the points within `radius` blocks of the given chunk, in block
+  /// coordinates. The grid is scaled up, such that each square of the grid is
+  /// `scale` blocks wide.
+  pub fn points_near_chunk(
+    &self,
+    seed: u64,
+    chunk_pos: ChunkPos,
+    scale: f64,
+    radius: i32,
+  ) -> impl Iterator<Item = (f64, f64)> + '_ {
+    let min_pos = chunk_pos.min_block_pos();
+    let min_x = ((min_pos.x - radius) as f64) / scale;
+    let min_z = ((min_pos.z - radius) as f64) / scale;
+    let max_x = ((min_pos.x + 16 + radius) as f64) / scale;
+    let max_z = ((min_pos.z + 16 + radius) as f64) / scale;
+
+    self.points_in_area(seed, min_x, min_z, max_x, max_z).map(move |(x, z)| (x * scale, z * scale))
+  }
+
   /// Returns the location of a point in the given square of the grid.
-  fn point_in_square(&self, seed: u64, x: i32, y: i32) -> (f64, f64) {
+  pub fn point_in_square(&self, seed: u64, x: i32, y: i32) -> (f64, f64) {
     let seed = seed.wrapping_add((x as u64) << 32).wrapping_add(y as u64);
 
     let number = Rng::new(seed).next();
@@ -62,6 +118,19 @@ impl PointGrid {
   }
 }
 
+impl PointDistribution for PointGrid {
+  fn points_in_area(
+    &self,
+    seed: u64,
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+  ) -> Box<dyn Iterator<Item = (f64, f64)> + '_> {
+    Box::new(PointGrid::points_in_area(self, seed, min_x, min_y, max_x, max_y))
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
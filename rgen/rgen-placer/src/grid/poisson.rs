@@ -0,0 +1,119 @@
+use rgen_base::Pos;
+
+use super::{PointDistribution, PointGrid};
+use crate::{Random, Rng, derive_pos_seed};
+
+/// A Poisson-disk distribution, where no two points are closer than
+/// `min_distance` to each other.
+///
+/// This starts with a [`PointGrid`], and then removes points that are too close
+/// to a neighboring point with a higher priority. The priority of each point is
+/// random, so this only depends on the nearby squares, and stays the same
+/// across chunk borders.
+///
+/// Because points are removed, there will be fewer than one point per square
+/// on average. The larger `min_distance` is, the fewer points there will be.
+pub struct PoissonDisk {
+  min_distance:      f64,
+  points_per_square: f64,
+}
+
+impl PoissonDisk {
+  pub fn new(min_distance: f64) -> Self {
+    let mut poisson = PoissonDisk { min_distance, points_per_square: 1.0 };
+
+    // There's no simple formula for how many points are removed, so count them
+    // over a large enough area instead.
+    const SIZE: f64 = 32.0;
+    let count = poisson.points_in_area(0, 0.0, 0.0, SIZE, SIZE).count();
+    poisson.points_per_square = count as f64 / (SIZE * SIZE);
+
+    poisson
+  }
+
+  pub fn min_distance(&self) -> f64 { self.min_distance }
+
+  pub fn points_in_area(
+    &self,
+    seed: u64,
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+  ) -> impl Iterator<Item = (f64, f64)> + '_ {
+    PointGrid.points_in_area(seed, min_x, min_y, max_x, max_y).filter(move |&p| self.keep(seed, p))
+  }
+
+  fn keep(&self, seed: u64, p: (f64, f64)) -> bool {
+    let x = p.0.floor() as i32;
+    let y = p.1.floor() as i32;
+    let own_priority = priority(seed, x, y);
+
+    let radius = self.min_distance.ceil() as i32;
+    for dx in -radius..=radius {
+      for dy in -radius..=radius {
+        if dx == 0 && dy == 0 {
+          continue;
+        }
+
+        let other = PointGrid.point_in_square(seed, x + dx, y + dy);
+        let dist_squared = (p.0 - other.0).powi(2) + (p.1 - other.1).powi(2);
+        if dist_squared < self.min_distance.powi(2) && priority(seed, x + dx, y + dy) > own_priority
+        {
+          return false;
+        }
+      }
+    }
+
+    true
+  }
+}
+
+fn priority(seed: u64, x: i32, y: i32) -> u64 {
+  Rng::new(derive_pos_seed(seed, Pos::new(x, 0, y))).next()
+}
+
+impl PointDistribution for PoissonDisk {
+  fn points_per_square(&self) -> f64 { self.points_per_square }
+
+  fn points_in_area(
+    &self,
+    seed: u64,
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+  ) -> Box<dyn Iterator<Item = (f64, f64)> + '_> {
+    Box::new(PoissonDisk::points_in_area(self, seed, min_x, min_y, max_x, max_y))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn points_are_spaced_out() {
+    const SEED: u64 = 1234;
+    let poisson = PoissonDisk::new(0.8);
+
+    let points: Vec<_> = poisson.points_in_area(SEED, 0.0, 0.0, 32.0, 32.0).collect();
+    assert!(!points.is_empty());
+
+    for (i, a) in points.iter().enumerate() {
+      for b in &points[i + 1..] {
+        let dist = ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt();
+        assert!(dist >= 0.8, "{a:?} and {b:?} are too close");
+      }
+    }
+
+    let density = points.len() as f64 / (32.0 * 32.0);
+    assert!((density - poisson.points_per_square).abs() < 0.1, "density = {density}");
+
+    // Smaller areas return the same points.
+    let small: Vec<_> = poisson.points_in_area(SEED, 4.0, 4.0, 8.0, 8.0).collect();
+    for p in small {
+      assert!(points.contains(&p));
+    }
+  }
+}
@@ -0,0 +1,104 @@
+use rgen_base::Pos;
+
+use super::PointDistribution;
+use crate::{Random, Rng, derive_pos_seed, derive_seed, noise::NoiseGenerator};
+
+/// Removes points from another distribution, based on a density map. The
+/// density map is sampled at each point, and is the chance that the point will
+/// be kept. So values of 0 or less will remove every point, and values of 1 or
+/// more will keep every point.
+///
+/// The density map is sampled in the same units as the points, so one unit is
+/// about the distance between two points.
+pub struct Weighted<D, N> {
+  distribution:      D,
+  density:           N,
+  points_per_square: f64,
+}
+
+impl<D: PointDistribution, N: NoiseGenerator<Output = f64> + Send + Sync> Weighted<D, N> {
+  pub fn new(distribution: D, density: N) -> Self {
+    // The average density depends on the noise, so sample it over a large area.
+    // The area needs to be much larger than the features of the noise, or a
+    // single dense patch would skew the average.
+    const SIZE: usize = 256;
+    const STEP: usize = 4;
+    let mut total = 0.0;
+    for x in (0..SIZE).step_by(STEP) {
+      for y in (0..SIZE).step_by(STEP) {
+        total += density.generate(x as f64, y as f64).clamp(0.0, 1.0);
+      }
+    }
+    let avg_density = total / (SIZE / STEP).pow(2) as f64;
+
+    Weighted {
+      points_per_square: distribution.points_per_square() * avg_density,
+      distribution,
+      density,
+    }
+  }
+
+  pub fn points_in_area(
+    &self,
+    seed: u64,
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+  ) -> impl Iterator<Item = (f64, f64)> + '_ {
+    let weight_seed = derive_seed(seed, "weighted");
+
+    self.distribution.points_in_area(seed, min_x, min_y, max_x, max_y).filter(move |&p| {
+      let density = self.density.generate(p.0, p.1);
+
+      // Points are usually in different squares, but clustered distributions can
+      // have many points in one square, so use a finer grid for the seed.
+      let pos = Pos::new((p.0 * 1024.0) as i32, 0, (p.1 * 1024.0) as i32);
+      Rng::new(derive_pos_seed(weight_seed, pos)).range(0.0..1.0) < density
+    })
+  }
+}
+
+impl<D: PointDistribution, N: NoiseGenerator<Output = f64> + Send + Sync> PointDistribution
+  for Weighted<D, N>
+{
+  fn points_per_square(&self) -> f64 { self.points_per_square }
+
+  fn points_in_area(
+    &self,
+    seed: u64,
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+  ) -> Box<dyn Iterator<Item = (f64, f64)> + '_> {
+    Box::new(Weighted::points_in_area(self, seed, min_x, min_y, max_x, max_y))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    grid::PointGrid,
+    noise::{OctavedNoise, OpenSimplexNoise},
+  };
+
+  #[test]
+  fn points_per_square_matches_density() {
+    // The noise is below 0 about half of the time, and rarely gets close to 1, so
+    // this keeps around a tenth of the points.
+    let weighted =
+      Weighted::new(PointGrid, OctavedNoise::<OpenSimplexNoise, 3>::new(1234, 1.0 / 16.0));
+    assert!((0.05..0.2).contains(&weighted.points_per_square), "{}", weighted.points_per_square);
+
+    const SIZE: f64 = 512.0;
+    let count = weighted.points_in_area(5678, 0.0, 0.0, SIZE, SIZE).count();
+    let density = count as f64 / (SIZE * SIZE);
+    assert!(
+      (density - weighted.points_per_square).abs() < 0.02,
+      "density = {density}, expected {}",
+      weighted.points_per_square
+    );
+  }
+}
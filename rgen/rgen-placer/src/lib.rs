@@ -2,6 +2,7 @@
 
 mod chunk;
 pub mod chunk_placer;
+pub mod condition;
 pub mod grid;
 pub mod noise;
 pub mod placer;
@@ -10,7 +11,7 @@ mod rng;
 pub use chunk::*;
 use rgen_base::{ChunkPos, Pos};
 use rgen_world::{PartialWorld, UndoError};
-pub use rng::{Random, Rng};
+pub use rng::{Random, Rng, derive_index_seed, derive_pos_seed, derive_seed};
 
 pub type Result = std::result::Result<(), UndoError>;
 
@@ -34,14 +35,34 @@ pub trait Placer: Send + Sync {
   /// chunk.
   fn avg_per_chunk(&self) -> f64 { 1.0 }
 
+  /// How this placer is spread out in the world. Defaults to a [`PointGrid`],
+  /// which places one point in each grid square.
+  ///
+  /// [`PointGrid`]: grid::PointGrid
+  fn distribution(&self) -> Box<dyn grid::PointDistribution> { Box::new(grid::PointGrid) }
+
   /// Places the blocks in the world at the given position.
   fn place(&self, world: &mut PartialWorld, rng: &mut Rng, pos: Pos) -> Result;
+
+  /// Only runs this placer when the given condition passes.
+  ///
+  /// See the [`condition`] module for the available conditions.
+  fn when<C: condition::Condition>(self, condition: C) -> condition::Conditional<Self, C>
+  where
+    Self: Sized,
+  {
+    condition::Conditional { placer: self, condition }
+  }
 }
 
 /// A ChunkPlacer places a set of decorations on a single chunk.
 ///
 /// This is less flexible than a `Placer`, because it can only access a single
 /// chunk, but it ends up being faster, as it will be run in parallel.
+///
+/// The `rng` passed to `place` is seeded uniquely for each placer and chunk.
+/// Placers that need randomness that is consistent across chunk borders should
+/// be given their own seed when they are constructed.
 pub trait ChunkPlacer: Send + Sync {
   fn place(&self, chunk: &mut BiomeCachedChunk, rng: &mut Rng, chunk_pos: ChunkPos);
 }
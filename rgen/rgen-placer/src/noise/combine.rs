@@ -0,0 +1,219 @@
+use std::marker::PhantomData;
+
+use rgen_spline::{Interpolation, Spline, SplineStorage};
+
+use super::{NoiseGenerator, NoiseGenerator3D};
+
+/// The minimum of two noise maps.
+#[derive(Debug, Copy, Clone)]
+pub struct MinNoise<A, B>(pub A, pub B);
+
+/// The maximum of two noise maps.
+#[derive(Debug, Copy, Clone)]
+pub struct MaxNoise<A, B>(pub A, pub B);
+
+/// Blends between two noise maps, using a third noise map as the mask. A mask
+/// of -1.0 returns `a`, and a mask of 1.0 returns `b`.
+#[derive(Debug, Copy, Clone)]
+pub struct BlendNoise<A, B, Mask> {
+  pub a:    A,
+  pub b:    B,
+  pub mask: Mask,
+}
+
+/// Selects between two noise maps, using a third noise map as the mask. Where
+/// the mask is below `threshold`, this returns `a`, and above it returns `b`.
+///
+/// Within `falloff` of the threshold, the two are blended together, to avoid a
+/// sharp edge. A `falloff` of 0 makes a sharp edge.
+#[derive(Debug, Copy, Clone)]
+pub struct SelectNoise<A, B, Mask> {
+  pub a:    A,
+  pub b:    B,
+  pub mask: Mask,
+
+  pub threshold: f64,
+  pub falloff:   f64,
+}
+
+/// Remaps a noise map through a spline. The noise is mapped from [-1, 1] to
+/// [0, 1] before sampling the spline, and the output of the spline is returned
+/// as-is.
+#[derive(Debug, Clone)]
+pub struct RemapNoise<Noise, T, I> {
+  pub noise:  Noise,
+  pub spline: Spline<T>,
+
+  _interpolation: PhantomData<I>,
+}
+
+impl<N, T, I> RemapNoise<N, T, I> {
+  pub fn new(noise: N, spline: Spline<T>) -> Self {
+    RemapNoise { noise, spline, _interpolation: PhantomData }
+  }
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 { a + (b - a) * t }
+
+impl<A, B, M> SelectNoise<A, B, M> {
+  fn select(&self, mask: f64, a: impl FnOnce() -> f64, b: impl FnOnce() -> f64) -> f64 {
+    if mask < self.threshold - self.falloff {
+      a()
+    } else if mask > self.threshold + self.falloff || self.falloff <= 0.0 {
+      b()
+    } else {
+      let t = (mask - (self.threshold - self.falloff)) / (self.falloff * 2.0);
+      lerp(a(), b(), t)
+    }
+  }
+}
+
+impl<A: NoiseGenerator<Output = f64>, B: NoiseGenerator<Output = f64>> NoiseGenerator
+  for MinNoise<A, B>
+{
+  type Output = f64;
+
+  fn generate(&self, x: f64, y: f64) -> f64 { self.0.generate(x, y).min(self.1.generate(x, y)) }
+}
+
+impl<A: NoiseGenerator3D, B: NoiseGenerator3D> NoiseGenerator3D for MinNoise<A, B> {
+  fn generate_3d(&self, x: f64, y: f64, z: f64) -> f64 {
+    self.0.generate_3d(x, y, z).min(self.1.generate_3d(x, y, z))
+  }
+}
+
+impl<A: NoiseGenerator<Output = f64>, B: NoiseGenerator<Output = f64>> NoiseGenerator
+  for MaxNoise<A, B>
+{
+  type Output = f64;
+
+  fn generate(&self, x: f64, y: f64) -> f64 { self.0.generate(x, y).max(self.1.generate(x, y)) }
+}
+
+impl<A: NoiseGenerator3D, B: NoiseGenerator3D> NoiseGenerator3D for MaxNoise<A, B> {
+  fn generate_3d(&self, x: f64, y: f64, z: f64) -> f64 {
+    self.0.generate_3d(x, y, z).max(self.1.generate_3d(x, y, z))
+  }
+}
+
+impl<
+  A: NoiseGenerator<Output = f64>,
+  B: NoiseGenerator<Output = f64>,
+  M: NoiseGenerator<Output = f64>,
+> NoiseGenerator for BlendNoise<A, B, M>
+{
+  type Output = f64;
+
+  fn generate(&self, x: f64, y: f64) -> f64 {
+    let t = self.mask.generate(x, y) * 0.5 + 0.5;
+    lerp(self.a.generate(x, y), self.b.generate(x, y), t)
+  }
+}
+
+impl<A: NoiseGenerator3D, B: NoiseGenerator3D, M: NoiseGenerator3D> NoiseGenerator3D
+  for BlendNoise<A, B, M>
+{
+  fn generate_3d(&self, x: f64, y: f64, z: f64) -> f64 {
+    let t = self.mask.generate_3d(x, y, z) * 0.5 + 0.5;
+    lerp(self.a.generate_3d(x, y, z), self.b.generate_3d(x, y, z), t)
+  }
+}
+
+impl<
+  A: NoiseGenerator<Output = f64>,
+  B: NoiseGenerator<Output = f64>,
+  M: NoiseGenerator<Output = f64>,
+> NoiseGenerator for SelectNoise<A, B, M>
+{
+  type Output = f64;
+
+  fn generate(&self, x: f64, y: f64) -> f64 {
+    self.select(self.mask.generate(x, y), || self.a.generate(x, y), || self.b.generate(x, y))
+  }
+}
+
+impl<A: NoiseGenerator3D, B: NoiseGenerator3D, M: NoiseGenerator3D> NoiseGenerator3D
+  for SelectNoise<A, B, M>
+{
+  fn generate_3d(&self, x: f64, y: f64, z: f64) -> f64 {
+    self.select(
+      self.mask.generate_3d(x, y, z),
+      || self.a.generate_3d(x, y, z),
+      || self.b.generate_3d(x, y, z),
+    )
+  }
+}
+
+impl<N: NoiseGenerator<Output = f64>, T: SplineStorage, I: Interpolation> NoiseGenerator
+  for RemapNoise<N, T, I>
+{
+  type Output = f64;
+
+  fn generate(&self, x: f64, y: f64) -> f64 {
+    let v = (self.noise.generate(x, y) * 0.5 + 0.5).clamp(0.0, 1.0);
+    self.spline.sample::<I>(v)
+  }
+}
+
+impl<N: NoiseGenerator3D, T: SplineStorage, I: Interpolation> NoiseGenerator3D
+  for RemapNoise<N, T, I>
+{
+  fn generate_3d(&self, x: f64, y: f64, z: f64) -> f64 {
+    let v = (self.noise.generate_3d(x, y, z) * 0.5 + 0.5).clamp(0.0, 1.0);
+    self.spline.sample::<I>(v)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rgen_spline::Cosine;
+
+  use crate::noise::{OctavedNoise, OpenSimplexNoise};
+
+  use super::*;
+
+  fn noise(seed: u64) -> OctavedNoise<OpenSimplexNoise, 3> { OctavedNoise::new(seed, 1.0 / 16.0) }
+
+  /// Checks that the noise is in [-1, 1], and that it doesn't jump between
+  /// nearby samples.
+  fn check(noise: &impl NoiseGenerator<Output = f64>) {
+    for x in 0..100 {
+      for y in 0..100 {
+        let (x, y) = (x as f64 * 0.7, y as f64 * 0.7);
+        let v = noise.generate(x, y);
+        assert!((-1.0..=1.0).contains(&v), "v = {}", v);
+
+        let next = noise.generate(x + 0.01, y);
+        assert!((v - next).abs() < 0.05, "{v} jumps to {next}");
+      }
+    }
+  }
+
+  #[test]
+  fn combined_noise_in_range() {
+    check(&MinNoise(noise(0), noise(1)));
+    check(&MaxNoise(noise(0), noise(1)));
+    check(&BlendNoise { a: noise(0), b: noise(1), mask: noise(2) });
+    check(&SelectNoise {
+      a:         noise(0),
+      b:         noise(1),
+      mask:      noise(2),
+      threshold: 0.2,
+      falloff:   0.1,
+    });
+
+    static SPLINE: Spline<&'static [(f64, f64)]> =
+      Spline::new(&[(0.0, -1.0), (0.5, 0.5), (1.0, 1.0)]);
+    check(&RemapNoise::<_, _, Cosine>::new(noise(0), SPLINE));
+  }
+
+  #[test]
+  fn select_without_falloff() {
+    let select =
+      SelectNoise { a: (), b: (), mask: (), threshold: 0.2, falloff: 0.0 };
+
+    assert_eq!(select.select(0.1, || -1.0, || 1.0), -1.0);
+    assert_eq!(select.select(0.2, || -1.0, || 1.0), 1.0);
+    assert_eq!(select.select(0.3, || -1.0, || 1.0), 1.0);
+  }
+}
@@ -0,0 +1,181 @@
+use super::{NoiseGenerator, NoiseGenerator3D, SeededNoise};
+use crate::derive_index_seed;
+
+/// Ridged multifractal noise. This makes sharp ridges where the underlying
+/// noise crosses zero, which is useful for mountain ranges and canyons.
+///
+/// Each octave is weighted by the previous one, so the detail is focused on
+/// the ridges, and the valleys between them stay smooth.
+#[derive(Debug, Copy, Clone)]
+pub struct RidgedNoise<Noise, const O: usize> {
+  pub freq:   f64,
+  pub lacu:   f64,
+  pub gain:   f64,
+  pub layers: [Noise; O],
+}
+
+impl<N: SeededNoise, const O: usize> RidgedNoise<N, O> {
+  pub fn new(seed: u64, freq: f64) -> Self {
+    Self {
+      freq,
+      lacu: 2.0,
+      gain: 2.0,
+      layers: match (0..O)
+        .map(|i| N::new(derive_index_seed(seed, i as u64)))
+        .collect::<Vec<_>>()
+        .try_into()
+      {
+        Ok(layers) => layers,
+        Err(_) => unreachable!(),
+      },
+    }
+  }
+
+  pub fn with_lacu(mut self, lacu: f64) -> Self {
+    self.lacu = lacu;
+    self
+  }
+  pub fn with_gain(mut self, gain: f64) -> Self {
+    self.gain = gain;
+    self
+  }
+}
+
+impl<N, const O: usize> RidgedNoise<N, O> {
+  // Combines the octaves of noise, where `sample` samples the given octave.
+  fn ridged(&self, mut sample: impl FnMut(usize, f64) -> f64) -> f64 {
+    let mut res = 0.0;
+    let mut max = 0.0;
+    let mut weight = 1.0;
+    let mut freq = self.freq;
+
+    for octave in 0..O {
+      let signal = 1.0 - sample(octave, freq).abs();
+      let signal = signal * signal * weight;
+
+      let amplitude = 0.5_f64.powi(octave as i32);
+      res += signal * amplitude;
+      max += amplitude;
+
+      weight = (signal * self.gain).clamp(0.0, 1.0);
+      freq *= self.lacu;
+    }
+
+    // Map from [0, max] back to [-1, 1].
+    (res / max * 2.0 - 1.0).clamp(-1.0, 1.0)
+  }
+}
+
+impl<Noise: NoiseGenerator<Output = f64>, const O: usize> NoiseGenerator for RidgedNoise<Noise, O> {
+  type Output = f64;
+
+  fn generate(&self, x: f64, y: f64) -> f64 {
+    self.ridged(|octave, freq| self.layers[octave].generate(x * freq, y * freq))
+  }
+}
+
+impl<Noise: NoiseGenerator3D, const O: usize> NoiseGenerator3D for RidgedNoise<Noise, O> {
+  fn generate_3d(&self, x: f64, y: f64, z: f64) -> f64 {
+    self.ridged(|octave, freq| self.layers[octave].generate_3d(x * freq, y * freq, z * freq))
+  }
+}
+
+/// The absolute value of a noise map. This is in the range [0, 1].
+#[derive(Debug, Copy, Clone)]
+pub struct AbsNoise<Noise>(pub Noise);
+
+/// Billow noise, which is the absolute value of the noise, mapped back to [-1,
+/// 1]. This makes soft, rounded hills with sharp creases between them.
+#[derive(Debug, Copy, Clone)]
+pub struct BillowNoise<Noise>(pub Noise);
+
+/// Snaps a noise map into `steps` flat terraces, with a smooth slope between
+/// each terrace.
+#[derive(Debug, Copy, Clone)]
+pub struct TerraceNoise<Noise> {
+  noise: Noise,
+  /// The number of terraces. This is always at least 1.
+  steps: u32,
+
+  /// How much of each step is a slope. 0.0 makes sharp cliffs, and 1.0 makes
+  /// no terraces at all.
+  slope: f64,
+}
+
+impl<N> TerraceNoise<N> {
+  pub fn new(noise: N, steps: u32, slope: f64) -> Self {
+    assert!(steps > 0, "terrace noise must have at least one step");
+    TerraceNoise { noise, steps, slope: slope.clamp(0.0, 1.0) }
+  }
+
+  fn terrace(&self, v: f64) -> f64 {
+    // Map to [0, steps], so that each step is 1 unit wide.
+    let v = (v * 0.5 + 0.5) * self.steps as f64;
+    let step = v.floor();
+    let t = v - step;
+
+    // Flat for the first part of the step, then slope up to the next one.
+    let flat = 1.0 - self.slope;
+    let t = if t < flat { 0.0 } else { (t - flat) / self.slope };
+
+    ((step + t) / self.steps as f64) * 2.0 - 1.0
+  }
+}
+
+macro_rules! map_noise {
+  ($ty:ident, $self:ident, $v:ident => $map:expr, $noise:expr) => {
+    impl<Noise: NoiseGenerator<Output = f64>> NoiseGenerator for $ty<Noise> {
+      type Output = f64;
+
+      fn generate(&$self, x: f64, y: f64) -> f64 {
+        let $v = $noise.generate(x, y);
+        $map
+      }
+    }
+
+    impl<Noise: NoiseGenerator3D> NoiseGenerator3D for $ty<Noise> {
+      fn generate_3d(&$self, x: f64, y: f64, z: f64) -> f64 {
+        let $v = $noise.generate_3d(x, y, z);
+        $map
+      }
+    }
+  };
+}
+
+map_noise!(AbsNoise, self, v => v.abs(), self.0);
+map_noise!(BillowNoise, self, v => v.abs() * 2.0 - 1.0, self.0);
+map_noise!(TerraceNoise, self, v => self.terrace(v), self.noise);
+
+#[cfg(test)]
+mod tests {
+  use crate::noise::{OpenSimplexNoise, PerlinNoise};
+
+  use super::*;
+
+  #[test]
+  fn ridged_noise_in_range() {
+    let noise = RidgedNoise::<OpenSimplexNoise, 4>::new(0, 1.0 / 16.0);
+    let noise_3d = RidgedNoise::<PerlinNoise, 4>::new(0, 1.0 / 16.0);
+
+    for x in 0..100 {
+      for y in 0..100 {
+        let v = noise.generate(x as f64, y as f64);
+        assert!((-1.0..=1.0).contains(&v), "v = {}", v);
+
+        let v = noise_3d.generate_3d(x as f64, y as f64, 3.0);
+        assert!((-1.0..=1.0).contains(&v), "v = {}", v);
+      }
+    }
+  }
+
+  #[test]
+  fn terrace_is_flat() {
+    let terrace = TerraceNoise::new((), 4, 0.5);
+
+    assert_eq!(terrace.terrace(-1.0), -1.0);
+    assert_eq!(terrace.terrace(-0.9), -1.0);
+    assert_eq!(terrace.terrace(-0.5), -0.5);
+    assert!(terrace.terrace(-0.55) > -1.0);
+    assert!(terrace.terrace(-0.55) < -0.5);
+  }
+}
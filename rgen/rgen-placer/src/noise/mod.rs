@@ -1,14 +1,27 @@
+mod combine;
+mod fractal;
 mod octaved;
 mod open_simplex;
 mod perlin;
 mod shifted;
 mod voronoi;
+mod warped;
+mod worley;
 
+pub use combine::{BlendNoise, MaxNoise, MinNoise, RemapNoise, SelectNoise};
+pub use fractal::{AbsNoise, BillowNoise, RidgedNoise, TerraceNoise};
 pub use octaved::OctavedNoise;
 pub use open_simplex::OpenSimplexNoise;
 pub use perlin::PerlinNoise;
 pub use shifted::ShiftedNoise;
 pub use voronoi::VoronoiNoise;
+pub use warped::WarpedNoise;
+pub use worley::{WorleyNoise, WorleySample};
+
+/// The number of samples the batch methods work on at once. The math for each
+/// sample is done on fixed-size arrays of this many samples, so that the
+/// compiler can vectorize it.
+const LANES: usize = 8;
 
 pub trait SeededNoise {
   fn new(seed: u64) -> Self;
@@ -18,8 +31,50 @@ pub trait NoiseGenerator {
   type Output;
 
   fn generate(&self, x: f64, y: f64) -> Self::Output;
+
+  /// Fills `out` with samples along the X axis, starting at `(x, y)` and moving
+  /// `step` between each sample. This is the same as calling `generate` for
+  /// each point, but some generators can fill a whole row much faster.
+  fn generate_row(&self, x: f64, y: f64, step: f64, out: &mut [Self::Output]) {
+    for (i, v) in out.iter_mut().enumerate() {
+      *v = self.generate(x + i as f64 * step, y);
+    }
+  }
+
+  /// Fills a 16x16 grid of samples, starting at `(x, y)` and moving `step`
+  /// between each sample. The grid is indexed with `out[y][x]`.
+  fn generate_grid(&self, x: f64, y: f64, step: f64, out: &mut [[Self::Output; 16]; 16]) {
+    for (i, row) in out.iter_mut().enumerate() {
+      self.generate_row(x, y + i as f64 * step, step, row);
+    }
+  }
 }
 
 pub trait NoiseGenerator3D {
   fn generate_3d(&self, x: f64, y: f64, z: f64) -> f64;
+
+  /// Fills `out` with samples along the Y axis, starting at `(x, y, z)` and
+  /// moving `step` between each sample. This is the same as calling
+  /// `generate_3d` for each point, but some generators can fill a whole column
+  /// much faster.
+  fn generate_column_3d(&self, x: f64, y: f64, z: f64, step: f64, out: &mut [f64]) {
+    for (i, v) in out.iter_mut().enumerate() {
+      *v = self.generate_3d(x, y + i as f64 * step, z);
+    }
+  }
+
+  /// Fills a 16x16xN grid of samples, starting at `(x, y, z)` and moving `step`
+  /// between each sample. The length of `out` must be a multiple of 256, and
+  /// each column is stored next to each other, so the grid is indexed with
+  /// `out[(z * 16 + x) * height + y]`.
+  fn generate_grid_3d(&self, x: f64, y: f64, z: f64, step: f64, out: &mut [f64]) {
+    assert!(out.len().is_multiple_of(256), "grid must be 16x16xN");
+    let height = out.len() / 256;
+
+    for (i, column) in out.chunks_exact_mut(height).enumerate() {
+      let rel_x = (i % 16) as f64 * step;
+      let rel_z = (i / 16) as f64 * step;
+      self.generate_column_3d(x + rel_x, y, z + rel_z, step, column);
+    }
+  }
 }
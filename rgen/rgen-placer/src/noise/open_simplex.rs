@@ -14,12 +14,76 @@ impl SeededNoise for OpenSimplexNoise {
 impl NoiseGenerator for OpenSimplexNoise {
   type Output = f64;
 
-  fn generate(&self, x: f64, y: f64) -> f64 { noise_2(Vector2::new(x, y), &self.perm) }
+  fn generate(&self, x: f64, y: f64) -> f64 {
+    noise_2(Vector2::new(x, y), &self.perm, &mut Uncached)
+  }
+
+  fn generate_row(&self, x: f64, y: f64, step: f64, out: &mut [f64]) {
+    let mut cache = CellCache::<4>::new();
+    for (i, v) in out.iter_mut().enumerate() {
+      *v = noise_2(Vector2::new(x + i as f64 * step, y), &self.perm, &mut cache);
+    }
+  }
 }
 
 impl NoiseGenerator3D for OpenSimplexNoise {
   fn generate_3d(&self, x: f64, y: f64, z: f64) -> f64 {
-    noise_3(Vector3::new(x, y, z), &self.perm)
+    noise_3(Vector3::new(x, y, z), &self.perm, &mut Uncached)
+  }
+
+  fn generate_column_3d(&self, x: f64, y: f64, z: f64, step: f64, out: &mut [f64]) {
+    let mut cache = CellCache::<8>::new();
+    for (i, v) in out.iter_mut().enumerate() {
+      *v = noise_3(Vector3::new(x, y + i as f64 * step, z), &self.perm, &mut cache);
+    }
+  }
+}
+
+/// Looks up the gradient index of each corner of a cell.
+trait Corners {
+  /// Called with the origin of the cell before any corners are looked up.
+  fn enter(&mut self, _cell: [f64; 3]) {}
+
+  fn get(&mut self, corner: usize, hash: impl FnOnce() -> usize) -> usize;
+}
+
+/// Hashes every corner, which is the fastest for single samples.
+struct Uncached;
+
+impl Corners for Uncached {
+  fn get(&mut self, _corner: usize, hash: impl FnOnce() -> usize) -> usize { hash() }
+}
+
+/// The hashed corners of the last cell sampled. Samples next to each other are
+/// usually in the same cell, so batches only need to hash each corner once.
+///
+/// Corners are indexed by their offset from the cell origin, with `x + y * 2 +
+/// z * 4`.
+struct CellCache<const N: usize> {
+  cell:    [f64; 3],
+  indices: [usize; N],
+}
+
+impl<const N: usize> CellCache<N> {
+  const UNSET: usize = usize::MAX;
+
+  fn new() -> Self { CellCache { cell: [f64::NAN; 3], indices: [Self::UNSET; N] } }
+}
+
+impl<const N: usize> Corners for CellCache<N> {
+  /// Moves to the given cell, clearing the cache if it's a different cell.
+  fn enter(&mut self, cell: [f64; 3]) {
+    if self.cell != cell {
+      self.cell = cell;
+      self.indices = [Self::UNSET; N];
+    }
+  }
+
+  fn get(&mut self, corner: usize, hash: impl FnOnce() -> usize) -> usize {
+    if self.indices[corner] == Self::UNSET {
+      self.indices[corner] = hash();
+    }
+    self.indices[corner]
   }
 }
 
@@ -65,7 +129,7 @@ impl PermutationTable {
 }
 
 // See the `noise` crate: https://crates.io/crates/noise
-fn noise_2(point: Vector2, perm: &PermutationTable) -> f64 {
+fn noise_2(point: Vector2, perm: &PermutationTable, cache: &mut impl Corners) -> f64 {
   const STRETCH_CONSTANT: f64 = -0.211_324_865_405_187; //(1/sqrt(2+1)-1)/2;
   const SQUISH_CONSTANT: f64 = 0.366_025_403_784_439; //(sqrt(2+1)-1)/2;
   const NORM_CONSTANT: f64 = 1.0 / 14.0;
@@ -87,6 +151,7 @@ fn noise_2(point: Vector2, perm: &PermutationTable) -> f64 {
 
   // Floor to get grid coordinates of rhombus (stretched square) cell origin.
   let stretched_floor = stretched.floor();
+  cache.enter([stretched_floor.x, stretched_floor.y, 0.0]);
 
   // Skew out to get actual coordinates of rhombus origin. We'll need these later.
   let squish_offset = stretched_floor.sum() * SQUISH_CONSTANT;
@@ -106,7 +171,8 @@ fn noise_2(point: Vector2, perm: &PermutationTable) -> f64 {
       {
         let offset = Vector2::new($x, $y);
         let vertex = stretched_floor + offset;
-        let index = perm.hash(&[vertex.x as isize, vertex.y as isize]);
+        let corner = $x as usize + $y as usize * 2;
+        let index = cache.get(corner, || perm.hash(&[vertex.x as isize, vertex.y as isize]));
         let dpos = rel_pos - (Vector2::broadcast(SQUISH_CONSTANT) * offset.sum()) - offset;
 
         surflet(index, dpos)
@@ -141,7 +207,7 @@ fn noise_2(point: Vector2, perm: &PermutationTable) -> f64 {
   value * NORM_CONSTANT
 }
 
-fn noise_3(point: Vector3, perm: &PermutationTable) -> f64 {
+fn noise_3(point: Vector3, perm: &PermutationTable, cache: &mut impl Corners) -> f64 {
   const STRETCH_CONSTANT: f64 = -1.0 / 6.0; //(1/sqrt(3+1)-1)/3;
   const SQUISH_CONSTANT: f64 = 1.0 / 3.0; //(sqrt(3+1)-1)/3;
   const NORM_CONSTANT: f64 = 1.0 / 14.0;
@@ -165,6 +231,7 @@ fn noise_3(point: Vector3, perm: &PermutationTable) -> f64 {
   // Floor to get simplectic honeycomb coordinates of rhombohedron
   // (stretched cube) super-cell origin.
   let stretched_floor = stretched.floor();
+  cache.enter([stretched_floor.x, stretched_floor.y, stretched_floor.z]);
 
   // Skew out to get actual coordinates of rhombohedron origin. We'll need
   // these later.
@@ -189,7 +256,10 @@ fn noise_3(point: Vector3, perm: &PermutationTable) -> f64 {
       {
         let offset = Vector3::new($x, $y, $z);
         let vertex = stretched_floor + offset;
-        let index = perm.hash(&[vertex.x as isize, vertex.y as isize, vertex.z as isize]);
+        let corner = $x as usize + $y as usize * 2 + $z as usize * 4;
+        let index = cache.get(corner, || {
+          perm.hash(&[vertex.x as isize, vertex.y as isize, vertex.z as isize])
+        });
         let dpos = rel_pos - (Vector3::broadcast(SQUISH_CONSTANT) * offset.sum()) - offset;
 
         surflet(index, dpos)
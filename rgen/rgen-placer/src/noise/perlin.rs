@@ -1,4 +1,4 @@
-use super::{NoiseGenerator, NoiseGenerator3D, SeededNoise};
+use super::{LANES, NoiseGenerator, NoiseGenerator3D, SeededNoise};
 
 #[derive(Default, Debug, Copy, Clone)]
 pub struct PerlinNoise {
@@ -13,10 +13,10 @@ impl NoiseGenerator for PerlinNoise {
   type Output = f64;
 
   fn generate(&self, x: f64, y: f64) -> f64 {
-    let x0 = if x > 0.0 { x as i32 } else { (x - 1.0) as i32 };
+    let x0 = lattice(x);
     let x1 = x0 + 1;
 
-    let y0 = if y > 0.0 { y as i32 } else { (y - 1.0) as i32 };
+    let y0 = lattice(y);
     let y1 = y0 + 1;
 
     let xd = s_curve(x - x0 as f64);
@@ -32,17 +32,32 @@ impl NoiseGenerator for PerlinNoise {
 
     interpolate(v1, v2, yd)
   }
+
+  fn generate_row(&self, x: f64, y: f64, step: f64, out: &mut [f64]) {
+    let seed = self.seed as i32;
+
+    let y0 = lattice(y);
+    let y1 = y0 + 1;
+    let yd = s_curve(y - y0 as f64);
+
+    // Interpolating on the Y axis first means that each lattice point on the X
+    // axis only needs to be computed once, and then it can be shared by every
+    // sample between it and the next lattice point.
+    fill_lanes(x, step, out, |x| {
+      interpolate(generate_random_value(x, y0, seed), generate_random_value(x, y1, seed), yd)
+    });
+  }
 }
 
 impl NoiseGenerator3D for PerlinNoise {
   fn generate_3d(&self, x: f64, y: f64, z: f64) -> f64 {
-    let x0 = if x > 0.0 { x as i32 } else { (x - 1.0) as i32 };
+    let x0 = lattice(x);
     let x1 = x0 + 1;
 
-    let y0 = if y > 0.0 { y as i32 } else { (y - 1.0) as i32 };
+    let y0 = lattice(y);
     let y1 = y0 + 1;
 
-    let z0 = if z > 0.0 { z as i32 } else { (z - 1.0) as i32 };
+    let z0 = lattice(z);
     let z1 = z0 + 1;
 
     let xd = s_curve(x - x0 as f64);
@@ -70,6 +85,80 @@ impl NoiseGenerator3D for PerlinNoise {
 
     interpolate(v1, v2, zd)
   }
+
+  fn generate_column_3d(&self, x: f64, y: f64, z: f64, step: f64, out: &mut [f64]) {
+    let seed = self.seed as i32;
+
+    let x0 = lattice(x);
+    let x1 = x0 + 1;
+    let xd = s_curve(x - x0 as f64);
+
+    let z0 = lattice(z);
+    let z1 = z0 + 1;
+    let zd = s_curve(z - z0 as f64);
+
+    // Same as `generate_row`, interpolate the X and Z axes first, so that each
+    // lattice point on the Y axis is only computed once.
+    let plane = |y: i32| {
+      let v1 = interpolate(
+        generate_random_value_3d(x0, y, z0, seed),
+        generate_random_value_3d(x1, y, z0, seed),
+        xd,
+      );
+      let v2 = interpolate(
+        generate_random_value_3d(x0, y, z1, seed),
+        generate_random_value_3d(x1, y, z1, seed),
+        xd,
+      );
+      interpolate(v1, v2, zd)
+    };
+
+    fill_lanes(y, step, out, plane);
+  }
+}
+
+/// Fills `out` with samples along a single axis, starting at `start` and moving
+/// `step` between each sample. `line` returns the value at each lattice point
+/// on that axis.
+///
+/// This looks up the lattice points for a lane of samples first, and then
+/// interpolates the whole lane at once, so that the interpolation can be
+/// vectorized.
+fn fill_lanes(start: f64, step: f64, out: &mut [f64], line: impl Fn(i32) -> f64) {
+  let mut cell = lattice(start);
+  let mut low = line(cell);
+  let mut high = line(cell + 1);
+
+  for (i, out) in out.chunks_mut(LANES).enumerate() {
+    let mut lows = [0.0; LANES];
+    let mut highs = [0.0; LANES];
+
+    for (lane, t) in out.iter_mut().enumerate() {
+      let a = start + (i * LANES + lane) as f64 * step;
+      let a0 = lattice(a);
+      if a0 != cell {
+        (low, high) = if a0 == cell + 1 { (high, line(a0 + 1)) } else { (line(a0), line(a0 + 1)) };
+        cell = a0;
+      }
+
+      lows[lane] = low;
+      highs[lane] = high;
+      *t = a - a0 as f64;
+    }
+
+    match <&mut [f64; LANES]>::try_from(&mut *out) {
+      Ok(out) => {
+        for lane in 0..LANES {
+          out[lane] = interpolate(lows[lane], highs[lane], s_curve(out[lane]));
+        }
+      }
+      Err(_) => {
+        for (lane, v) in out.iter_mut().enumerate() {
+          *v = interpolate(lows[lane], highs[lane], s_curve(*v));
+        }
+      }
+    }
+  }
 }
 
 fn generate_random_value(x: i32, y: i32, seed: i32) -> f64 {
@@ -107,6 +196,10 @@ fn generate_random_value_3d(x: i32, y: i32, z: i32, seed: i32) -> f64 {
       / 1073741824.0)
 }
 
+// Returns the lattice point below `a`. Note that whole numbers less than or
+// equal to zero round down to the next lattice point.
+fn lattice(a: f64) -> i32 { a as i32 - (a <= 0.0) as i32 }
+
 fn s_curve(a: f64) -> f64 { a * a * (3.0 - 2.0 * a) }
 
 fn interpolate(v1: f64, v2: f64, a: f64) -> f64 { ((1.0 - a) * v1) + (a * v2) }
@@ -0,0 +1,91 @@
+use super::{NoiseGenerator, NoiseGenerator3D};
+
+/// Domain warping. Each axis is offset by a separate warp noise map, before
+/// sampling the base noise map.
+///
+/// Unlike [`ShiftedNoise`](super::ShiftedNoise), each axis gets its own noise
+/// map, so the X and Y offsets aren't correlated. The Z warp is only used for
+/// 3D noise.
+#[derive(Debug, Copy, Clone)]
+pub struct WarpedNoise<Noise, Warp> {
+  pub base: Noise,
+
+  pub warp_x: Warp,
+  pub warp_y: Warp,
+  pub warp_z: Warp,
+
+  /// The distance that each axis can be moved.
+  pub amount: f64,
+}
+
+impl<N, W> WarpedNoise<N, W> {
+  pub fn new(base: N, warp_x: W, warp_y: W, warp_z: W, amount: f64) -> Self {
+    WarpedNoise { base, warp_x, warp_y, warp_z, amount }
+  }
+}
+
+impl<Noise: NoiseGenerator, Warp: NoiseGenerator<Output = f64>> NoiseGenerator
+  for WarpedNoise<Noise, Warp>
+{
+  type Output = Noise::Output;
+
+  fn generate(&self, x: f64, y: f64) -> Noise::Output {
+    let warp_x = self.warp_x.generate(x, y) * self.amount;
+    let warp_y = self.warp_y.generate(x, y) * self.amount;
+
+    self.base.generate(x + warp_x, y + warp_y)
+  }
+}
+
+impl<Noise: NoiseGenerator3D, Warp: NoiseGenerator3D> NoiseGenerator3D
+  for WarpedNoise<Noise, Warp>
+{
+  fn generate_3d(&self, x: f64, y: f64, z: f64) -> f64 {
+    let warp_x = self.warp_x.generate_3d(x, y, z) * self.amount;
+    let warp_y = self.warp_y.generate_3d(x, y, z) * self.amount;
+    let warp_z = self.warp_z.generate_3d(x, y, z) * self.amount;
+
+    self.base.generate_3d(x + warp_x, y + warp_y, z + warp_z)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::noise::{OctavedNoise, OpenSimplexNoise, PerlinNoise};
+
+  use super::*;
+
+  #[test]
+  fn warped_noise_in_range() {
+    let noise = WarpedNoise::new(
+      OctavedNoise::<OpenSimplexNoise, 3>::new(0, 1.0 / 16.0),
+      OctavedNoise::<OpenSimplexNoise, 3>::new(1, 1.0 / 32.0),
+      OctavedNoise::<OpenSimplexNoise, 3>::new(2, 1.0 / 32.0),
+      OctavedNoise::<OpenSimplexNoise, 3>::new(3, 1.0 / 32.0),
+      8.0,
+    );
+    let noise_3d = WarpedNoise::new(
+      OctavedNoise::<PerlinNoise, 3>::new(0, 1.0 / 16.0),
+      OctavedNoise::<PerlinNoise, 3>::new(1, 1.0 / 32.0),
+      OctavedNoise::<PerlinNoise, 3>::new(2, 1.0 / 32.0),
+      OctavedNoise::<PerlinNoise, 3>::new(3, 1.0 / 32.0),
+      8.0,
+    );
+
+    for x in 0..100 {
+      for y in 0..100 {
+        let (x, y) = (x as f64 * 0.7, y as f64 * 0.7);
+
+        let v = noise.generate(x, y);
+        assert!((-1.0..=1.0).contains(&v), "v = {}", v);
+        let next = noise.generate(x + 0.01, y);
+        assert!((v - next).abs() < 0.05, "{v} jumps to {next}");
+
+        let v = noise_3d.generate_3d(x, y, 3.0);
+        assert!((-1.0..=1.0).contains(&v), "v = {}", v);
+        let next = noise_3d.generate_3d(x, y + 0.01, 3.0);
+        assert!((v - next).abs() < 0.05, "{v} jumps to {next}");
+      }
+    }
+  }
+}
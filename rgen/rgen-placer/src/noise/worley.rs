@@ -0,0 +1,172 @@
+use rgen_base::Pos;
+
+use crate::{Random, Rng, derive_pos_seed};
+
+use super::{NoiseGenerator, SeededNoise};
+
+/// Worley (cellular) noise. The plane is split up into cells, each with a
+/// random feature point, and each sample returns the distance to the nearest
+/// feature points.
+///
+/// Unlike [`VoronoiNoise`](super::VoronoiNoise), the points are derived from a
+/// hash of each cell, so this never repeats.
+#[derive(Debug, Copy, Clone)]
+pub struct WorleyNoise {
+  pub seed: u64,
+  pub freq: f64,
+}
+
+/// A single sample of [`WorleyNoise`]. All distances are in the same units as
+/// the input coordinates.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct WorleySample {
+  /// The distance to the closest feature point.
+  pub f1: f64,
+  /// The distance to the second closest feature point.
+  pub f2: f64,
+
+  /// A unique id for the closest cell.
+  pub id:     u64,
+  /// The position of the closest feature point.
+  pub center: (f64, f64),
+}
+
+impl WorleySample {
+  /// Returns the distance to the edge of the cell, `f2 - f1`. This is 0 on the
+  /// border between two cells.
+  pub fn edge(&self) -> f64 { self.f2 - self.f1 }
+}
+
+impl SeededNoise for WorleyNoise {
+  fn new(seed: u64) -> Self { WorleyNoise { seed, freq: 1.0 } }
+}
+
+impl WorleyNoise {
+  pub fn with_freq(mut self, freq: f64) -> Self {
+    self.freq = freq;
+    self
+  }
+
+  /// Returns the feature point and id of the given cell.
+  fn cell(&self, x: i32, y: i32) -> ((f64, f64), u64) {
+    let mut rng = Rng::new(derive_pos_seed(self.seed, Pos::new(x, 0, y)));
+    let px = rng.range(0.0..1.0);
+    let py = rng.range(0.0..1.0);
+    ((f64::from(x) + px, f64::from(y) + py), rng.next())
+  }
+}
+
+impl NoiseGenerator for WorleyNoise {
+  type Output = WorleySample;
+
+  fn generate(&self, x: f64, y: f64) -> WorleySample {
+    let (sx, sy) = (x * self.freq, y * self.freq);
+    let (cx, cy) = (sx.floor() as i32, sy.floor() as i32);
+
+    let mut f1 = f64::MAX;
+    let mut f2 = f64::MAX;
+    let mut closest = ((0.0, 0.0), 0);
+
+    // The closest point is at most sqrt(2) away, and the second closest at most
+    // sqrt(5) away (there is always a point in the next cell over), so both are
+    // within 2 cells. The corners of the 5x5 area can be skipped: the point in
+    // our own cell and the point in the diagonal neighbour towards a corner are
+    // both strictly closer than the corner's point on each axis, so the corner
+    // can never be the closest or second closest.
+    for dx in -2_i32..=2 {
+      for dy in -2_i32..=2 {
+        if dx.abs() == 2 && dy.abs() == 2 {
+          continue;
+        }
+
+        let (point, id) = self.cell(cx + dx, cy + dy);
+        let dist = ((point.0 - sx).powi(2) + (point.1 - sy).powi(2)).sqrt();
+
+        if dist < f1 {
+          f2 = f1;
+          f1 = dist;
+          closest = (point, id);
+        } else if dist < f2 {
+          f2 = dist;
+        }
+      }
+    }
+
+    let ((px, py), id) = closest;
+    WorleySample {
+      f1: f1 / self.freq,
+      f2: f2 / self.freq,
+      id,
+      center: (px / self.freq, py / self.freq),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn worley_distances() {
+    let noise = WorleyNoise::new(1234).with_freq(1.0 / 16.0);
+
+    for x in -50..50 {
+      for y in -50..50 {
+        let (x, y) = (x as f64 * 1.7, y as f64 * 1.3);
+        let sample = noise.generate(x, y);
+
+        assert!(sample.f1 <= sample.f2);
+        assert!(sample.edge() >= 0.0);
+
+        let dist = ((sample.center.0 - x).powi(2) + (sample.center.1 - y).powi(2)).sqrt();
+        assert!((dist - sample.f1).abs() < 1e-9);
+
+        // The closest point is the same cell.
+        assert_eq!(noise.generate(sample.center.0, sample.center.1).id, sample.id);
+      }
+    }
+  }
+
+  #[test]
+  fn finds_closest_points() {
+    let noise = WorleyNoise::new(1234);
+
+    for x in -100..100 {
+      for y in -100..100 {
+        let (x, y) = (x as f64 * 0.37, y as f64 * 0.29);
+        let sample = noise.generate(x, y);
+
+        // Check against every cell in a much larger area.
+        let mut dists = vec![];
+        for cx in x.floor() as i32 - 4..=x.floor() as i32 + 4 {
+          for cy in y.floor() as i32 - 4..=y.floor() as i32 + 4 {
+            let (point, _) = noise.cell(cx, cy);
+            dists.push(((point.0 - x).powi(2) + (point.1 - y).powi(2)).sqrt());
+          }
+        }
+        dists.sort_by(f64::total_cmp);
+
+        assert_eq!(sample.f1, dists[0]);
+        assert_eq!(sample.f2, dists[1]);
+      }
+    }
+  }
+
+  #[test]
+  fn edge_is_continuous() {
+    let noise = WorleyNoise::new(1234).with_freq(1.0 / 16.0);
+
+    // Both distances change by at most the distance moved, so the edge changes by
+    // at most twice that. A missed feature point would show up as a jump.
+    const STEP: f64 = 0.05;
+    for y in -10..10 {
+      let y = y as f64 * 3.1;
+      let mut prev = noise.generate(-200.0, y).edge();
+      for x in 1..8000 {
+        let edge = noise.generate(-200.0 + x as f64 * STEP, y).edge();
+        assert!((edge - prev).abs() <= 2.0 * STEP + 1e-9, "edge jumped from {prev} to {edge}");
+        prev = edge;
+      }
+    }
+  }
+}
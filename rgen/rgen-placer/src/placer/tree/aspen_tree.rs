@@ -5,31 +5,34 @@ use rgen_world::{PartialWorld, UndoError};
 use crate::{Placer, Random, Result, Rng};
 
 pub struct AspenTree {
-  pub place_above:   BlockFilter,
-  pub trunk:         BlockState,
-  pub leaves:        BlockState,
-  pub avg_per_chunk: f64,
-  pub drapes_short:  Vec<Structure>,
-  pub drapes_long:   Vec<Structure>,
+  pub place_above:       BlockFilter,
+  pub trunk:             BlockState,
+  pub leaves:            BlockState,
+  pub avg_per_chunk:     f64,
+  pub drapes_short:      Vec<Structure>,
+  pub drapes_long:       Vec<Structure>,
+  /// The blocks that drapes can hang over.
+  pub drape_replaceable: BlockFilter,
 }
 
 impl Default for AspenTree {
   fn default() -> Self {
     AspenTree {
-      avg_per_chunk: 13.0, //40.0,
-      place_above:   block![grass].into(),
-      trunk:         block![log[2]],
-      leaves:        block![rgen:leaves3[0]],
-      drapes_short:  vec![
-        rgen_llama::parse(include_str!("structure/drape_aspen_s_0.ll")),
-        rgen_llama::parse(include_str!("structure/drape_aspen_s_1.ll")),
-        rgen_llama::parse(include_str!("structure/drape_aspen_s_2.ll")),
+      avg_per_chunk:     13.0, //40.0,
+      place_above:       block![grass].into(),
+      trunk:             block![log[2]],
+      leaves:            block![rgen:leaves3[0]],
+      drapes_short:      vec![
+        rgen_llama::parse(include_str!("structure/drape_aspen_s_0.ll")).unwrap(),
+        rgen_llama::parse(include_str!("structure/drape_aspen_s_1.ll")).unwrap(),
+        rgen_llama::parse(include_str!("structure/drape_aspen_s_2.ll")).unwrap(),
       ],
-      drapes_long:   vec![
-        rgen_llama::parse(include_str!("structure/drape_aspen_l_0.ll")),
-        rgen_llama::parse(include_str!("structure/drape_aspen_l_1.ll")),
-        rgen_llama::parse(include_str!("structure/drape_aspen_l_2.ll")),
+      drapes_long:       vec![
+        rgen_llama::parse(include_str!("structure/drape_aspen_l_0.ll")).unwrap(),
+        rgen_llama::parse(include_str!("structure/drape_aspen_l_1.ll")).unwrap(),
+        rgen_llama::parse(include_str!("structure/drape_aspen_l_2.ll")).unwrap(),
       ],
+      drape_replaceable: [block![air], block![tallgrass], block![rgen:leaves3[0]]].into(),
     }
   }
 }
@@ -140,6 +143,7 @@ impl AspenTree {
       world.place_structure(
         pos + pos_by_rotation[rotation as usize] + Pos::new(0, -(drape.height() as i32), 0),
         &drape,
+        &self.drape_replaceable,
       );
     }
   }
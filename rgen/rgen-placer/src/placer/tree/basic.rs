@@ -1,7 +1,10 @@
 use rgen_base::{BlockFilter, BlockState, Pos};
 use rgen_world::{PartialWorld, UndoError};
 
-use crate::{Placer, Random, Result, Rng};
+use crate::{
+  Placer, Random, Result, Rng,
+  grid::{PointDistribution, PoissonDisk},
+};
 
 pub struct BasicTree {
   pub place_above:   BlockFilter,
@@ -26,6 +29,9 @@ impl Placer for BasicTree {
 
   fn avg_per_chunk(&self) -> f64 { self.avg_per_chunk }
 
+  // Spread trees out, so that they don't grow into each other.
+  fn distribution(&self) -> Box<dyn PointDistribution> { Box::new(PoissonDisk::new(0.8)) }
+
   fn place(&self, world: &mut PartialWorld, rng: &mut Rng, pos: Pos) -> Result {
     let height = rng.range(5..=8);
 
@@ -17,7 +17,9 @@ pub struct Sakura {
   pub avg_per_chunk: f64,
   pub large_size:    bool,
 
-  pub drapes: Vec<Structure>,
+  pub drapes:            Vec<Structure>,
+  /// The blocks that drapes can hang over.
+  pub drape_replaceable: BlockFilter,
 }
 
 impl Default for Sakura {
@@ -29,11 +31,12 @@ impl Default for Sakura {
       leaves:        block![rgen:leaves[2]],
       large_size:    true,
 
-      drapes: vec![
-        rgen_llama::parse(include_str!("structure/drape_1.ll")),
-        rgen_llama::parse(include_str!("structure/drape_2.ll")),
-        rgen_llama::parse(include_str!("structure/drape_3.ll")),
+      drapes:            vec![
+        rgen_llama::parse(include_str!("structure/drape_1.ll")).unwrap(),
+        rgen_llama::parse(include_str!("structure/drape_2.ll")).unwrap(),
+        rgen_llama::parse(include_str!("structure/drape_3.ll")).unwrap(),
       ],
+      drape_replaceable: [block![air], block![tallgrass], block![rgen:leaves[2]]].into(),
     }
   }
 }
@@ -124,7 +127,11 @@ impl Sakura {
     drape.rotate(rotation);
     // Listen. I don't want to know why this works. I shouldn't need to know why
     // this works. But it does.
-    world.place_structure(pos + Pos::new(dx * 3 - dz.abs() * 2, -2, dz * 3 - dx.abs() * 2), &drape);
+    world.place_structure(
+      pos + Pos::new(dx * 3 - dz.abs() * 2, -2, dz * 3 - dx.abs() * 2),
+      &drape,
+      &self.drape_replaceable,
+    );
   }
 
   // Note: this should really be replaced with a struct to hold all the fields of
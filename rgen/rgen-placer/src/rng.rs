@@ -1,3 +1,5 @@
+use rgen_base::Pos;
+
 // An insecure, fast random number generator.
 #[derive(Debug, Copy, Clone)]
 pub struct Rng {
@@ -8,6 +10,40 @@ impl Rng {
   pub fn new(seed: u64) -> Rng { Rng { seed } }
 }
 
+/// Derives a new seed from `seed` and the given name.
+///
+/// The result only depends on the inputs, so it is stable across runs, and it
+/// doesn't matter what order seeds are derived in. This is how each placer gets
+/// its own random stream, so that adding or removing a placer doesn't change
+/// the output of any other placers.
+pub fn derive_seed(seed: u64, name: &str) -> u64 {
+  // FNV-1a, which is then mixed together with the seed through the rng.
+  let mut hash = 0xcbf29ce484222325_u64;
+  for byte in name.bytes() {
+    hash ^= u64::from(byte);
+    hash = hash.wrapping_mul(0x100000001b3);
+  }
+
+  Rng::new(Rng::new(seed).next() ^ hash).next()
+}
+
+/// Derives a new seed from `seed` and the given index. This is for when there
+/// are many seeds for the same thing, like each octave of a noise map.
+pub fn derive_index_seed(seed: u64, index: u64) -> u64 {
+  Rng::new(Rng::new(seed).next() ^ index.wrapping_mul(0x9e3779b97f4a7c15)).next()
+}
+
+/// Derives a new seed from `seed` and the given position. Like
+/// [`derive_seed`], this is stable, and doesn't depend on the order it is
+/// called in.
+pub fn derive_pos_seed(seed: u64, pos: Pos) -> u64 {
+  let hash = (pos.x as u32 as u64).wrapping_mul(0x9e3779b97f4a7c15)
+    ^ (pos.y as u32 as u64).wrapping_mul(0xc2b2ae3d27d4eb4f)
+    ^ (pos.z as u32 as u64).wrapping_mul(0x165667b19e3779f9);
+
+  Rng::new(Rng::new(seed).next() ^ hash).next()
+}
+
 impl Random for Rng {
   // This is the wyhash generator. See
   // https://lemire.me/blog/2019/03/19/the-fastest-conventional-random-number-generator-that-can-pass-big-crush/
@@ -124,6 +160,24 @@ mod tests {
     assert_eq!(rng.range(0.0..=100.0), 38.638413541668285);
   }
 
+  #[test]
+  fn derive_seed_is_stable() {
+    assert_eq!(derive_seed(1234, "oak tree"), derive_seed(1234, "oak tree"));
+    assert_ne!(derive_seed(1234, "oak tree"), derive_seed(1234, "birch tree"));
+    assert_ne!(derive_seed(1234, "oak tree"), derive_seed(1235, "oak tree"));
+
+    assert_eq!(derive_index_seed(1234, 1), derive_index_seed(1234, 1));
+    assert_ne!(derive_index_seed(1234, 1), derive_index_seed(1234, 2));
+    // Octave 1 of one seed shouldn't be the same as octave 0 of the next seed.
+    assert_ne!(derive_index_seed(1234, 1), derive_index_seed(1235, 0));
+
+    let a = Pos::new(1, 2, 3);
+    let b = Pos::new(3, 2, 1);
+    assert_eq!(derive_pos_seed(1234, a), derive_pos_seed(1234, a));
+    assert_ne!(derive_pos_seed(1234, a), derive_pos_seed(1234, b));
+    assert_ne!(derive_pos_seed(1234, a), derive_pos_seed(1235, a));
+  }
+
   #[test]
   fn i32_range() {
     assert_eq!(5.mod_range_exclusive(0, 10), 5);
@@ -47,6 +47,9 @@ enum RenderMode {
 const MIN_ZOOM: f64 = 0.5;
 const MAX_ZOOM: f64 = 32.0;
 
+/// How far to search for structures when pressing `L`.
+const LOCATE_RADIUS: i32 = 4096;
+
 pub fn main() -> Result<(), String> {
   let arg = std::env::args().nth(1).unwrap_or("".to_string());
 
@@ -104,6 +107,9 @@ pub fn main() -> Result<(), String> {
 
   let mut settings = Settings { chunk_borders: false };
 
+  // The structures found with `L`, as a name and position.
+  let mut markers = Vec::<(&'static str, Pos)>::new();
+
   'main: loop {
     let mut redraw = false;
 
@@ -142,6 +148,20 @@ pub fn main() -> Result<(), String> {
           settings.chunk_borders = !settings.chunk_borders;
         }
 
+        Event::KeyDown { keycode: Some(Keycode::L), .. } => {
+          markers = world
+            .generator
+            .structure_names()
+            .filter_map(|name| {
+              Some((name, world.generator.locate_structure(name, hover_pos, LOCATE_RADIUS)?))
+            })
+            .collect();
+
+          for (name, pos) in &markers {
+            println!("Found {name} at {} {} {}", pos.x, pos.y, pos.z);
+          }
+        }
+
         Event::MouseButtonDown { x, y, .. } => drag_pos = Some((x, y)),
         Event::MouseButtonUp { .. } => drag_pos = None,
 
@@ -284,6 +304,17 @@ pub fn main() -> Result<(), String> {
       ))?;
     }
 
+    render.canvas.set_draw_color(Color::RGB(255, 0, 0));
+    for (name, pos) in &markers {
+      let x = (pos.x as f64 * zoom - view_coords.0 * zoom) as i32;
+      let y = (pos.z as f64 * zoom - view_coords.1 * zoom) as i32;
+
+      render.canvas.fill_rect(Rect::new(x - 4, y - 4, 9, 9))?;
+      if let Some(f) = &font {
+        FontRender { font: f, render: &mut render }.render(x + 8, y - 12, name);
+      }
+    }
+
     if settings.chunk_borders {
       let min_chunk = view_pos.chunk();
       let max_chunk = max_pos.chunk();
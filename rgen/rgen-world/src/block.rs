@@ -1,8 +1,10 @@
 //! All the tools to edit blocks in a world.
 
 use crate::{PartialWorld, PartialWorldStorage, StagedWorldStorage, UndoFrame};
-use rgen_base::{BlockInfo, BlockState, Chunk, ChunkPos, Pos, StateId};
-use rgen_llama::Structure;
+use rgen_base::{
+  BlockEntity, BlockFilter, BlockInfo, BlockKind, BlockState, Chunk, ChunkPos, Entity, Pos, StateId,
+};
+use rgen_llama::{Placement, Structure};
 
 impl StagedWorldStorage {
   pub(crate) fn chunk(&self, pos: ChunkPos) -> Option<&Chunk> {
@@ -35,6 +37,18 @@ impl PartialWorldStorage for &mut StagedWorldStorage {
   fn surfaces(&self, pos: Pos) -> &[u8] {
     if let Some(chunk) = self.chunk(pos.chunk()) { chunk.surfaces(pos.chunk_rel()) } else { &[] }
   }
+
+  fn set_block_entity(&mut self, pos: Pos, block_entity: BlockEntity) {
+    if let Some(chunk) = self.chunk_mut(pos.chunk()) {
+      chunk.set_block_entity(pos.chunk_rel(), block_entity);
+    }
+  }
+
+  fn add_entity(&mut self, pos: Pos, entity: Entity) {
+    if let Some(chunk) = self.chunk_mut(pos.chunk()) {
+      chunk.add_entity(pos.chunk_rel(), entity);
+    }
+  }
 }
 
 /// An error that will cause the current placement to be undone.
@@ -50,13 +64,39 @@ impl PartialWorld<'_> {
     self.storage.set(pos, self.info.encode(state.into()));
   }
 
+  /// Sets the block entity at the given position. The block itself should be
+  /// placed separately.
+  pub fn set_block_entity(&mut self, pos: Pos, block_entity: BlockEntity) {
+    match self.undo_stack.last_mut() {
+      Some(frame) => frame.block_entities.push((pos, block_entity)),
+      None => self.storage.set_block_entity(pos, block_entity),
+    }
+  }
+
+  /// Spawns an entity in the middle of the block at the given position.
+  pub fn add_entity(&mut self, pos: Pos, entity: Entity) {
+    match self.undo_stack.last_mut() {
+      Some(frame) => frame.entities.push((pos, entity)),
+      None => self.storage.add_entity(pos, entity),
+    }
+  }
+
   pub fn attempt<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T, UndoError>) -> Option<T> {
     self.undo_stack.push(UndoFrame::default());
     let res = f(self);
     let frame = self.undo_stack.pop().unwrap();
 
     match res {
-      Ok(v) => Some(v),
+      Ok(v) => {
+        for (pos, block_entity) in frame.block_entities {
+          self.set_block_entity(pos, block_entity);
+        }
+        for (pos, entity) in frame.entities {
+          self.add_entity(pos, entity);
+        }
+
+        Some(v)
+      }
       Err(UndoError) => {
         for (pos, state) in frame.blocks.into_iter().rev() {
           self.storage.set(pos, state);
@@ -67,16 +107,61 @@ impl PartialWorld<'_> {
     }
   }
 
-  // TODO: allow for an array of blocks to not be overridden
-  pub fn place_structure(&mut self, pos: Pos, structure: &Structure) {
+  /// Places a structure with its minimum corner at `pos`. Cells that should
+  /// only replace some blocks will only replace blocks in `replaceable`.
+  pub fn place_structure(&mut self, pos: Pos, structure: &Structure, replaceable: &BlockFilter) {
     for y in 0..structure.height() {
       for z in 0..structure.depth() {
         for x in 0..structure.width() {
           let rel_pos = Pos::new(x as i32, y as i32, z as i32);
-          let block = structure.get(rel_pos);
-          if block != BlockState::AIR {
-            self.set(pos + rel_pos, block);
-          }
+          self.place_structure_block(
+            pos + rel_pos,
+            structure.get(rel_pos),
+            structure.placement(rel_pos),
+            replaceable,
+          );
+        }
+      }
+    }
+
+    for (rel_pos, block_entity) in structure.block_entities() {
+      self.set_block_entity(pos + *rel_pos, block_entity.clone());
+    }
+    for (rel_pos, entity) in structure.entities() {
+      self.add_entity(pos + *rel_pos, entity.clone());
+    }
+  }
+
+  /// Places a single block of a structure, following its [`Placement`]. This
+  /// is useful for structures that need to transform their blocks before
+  /// placing them.
+  pub fn place_structure_block(
+    &mut self,
+    pos: Pos,
+    block: BlockState,
+    placement: Placement,
+    replaceable: &BlockFilter,
+  ) {
+    match placement {
+      Placement::Normal => {
+        if block.block != BlockKind::Air {
+          self.set(pos, block);
+        }
+      }
+      Placement::Keep => {}
+      Placement::Force => self.set(pos, block),
+      Placement::IfReplaceable => {
+        if replaceable.contains(self.get(pos)) {
+          self.set(pos, block);
+        }
+      }
+      Placement::Foundation => {
+        self.set(pos, block);
+
+        let mut below = pos.with_y(pos.y - 1);
+        while below.y >= 0 && replaceable.contains(self.get(below)) {
+          self.set(below, block);
+          below = below.with_y(below.y - 1);
         }
       }
     }
@@ -84,3 +169,86 @@ impl PartialWorld<'_> {
 
   pub fn surfaces(&mut self, pos: Pos) -> &[u8] { self.storage.surfaces(pos) }
 }
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use rgen_base::block;
+
+  use super::*;
+  use crate::Context;
+
+  #[derive(Default)]
+  struct TestStorage {
+    blocks: HashMap<(i32, i32, i32), StateId>,
+  }
+
+  impl PartialWorldStorage for TestStorage {
+    fn get(&self, pos: Pos) -> StateId {
+      self.blocks.get(&(pos.x, pos.y, pos.z)).copied().unwrap_or(StateId::AIR)
+    }
+    fn set(&mut self, pos: Pos, block: StateId) {
+      self.blocks.insert((pos.x, pos.y, pos.z), block);
+    }
+    fn surfaces(&self, _: Pos) -> &[u8] { &[] }
+  }
+
+  #[test]
+  fn placements() {
+    let ctx = Context::new_test(0);
+    let mut world = PartialWorld::new(&ctx.blocks, TestStorage::default());
+    let replaceable: BlockFilter = [block![air], block![tallgrass]].into();
+
+    // A floor of stone at Y=0, with tall grass on top at X=1, and dirt at X=2.
+    for x in 0..4 {
+      world.set(Pos::new(x, 0, 0), block![stone]);
+    }
+    world.set(Pos::new(1, 1, 0), block![tallgrass]);
+    world.set(Pos::new(2, 1, 0), block![dirt]);
+
+    // Normal cells replace anything, but don't place air.
+    world.place_structure_block(Pos::new(2, 1, 0), block![air], Placement::Normal, &replaceable);
+    assert_eq!(world.get(Pos::new(2, 1, 0)), block![dirt]);
+    world.place_structure_block(Pos::new(2, 1, 0), block![planks], Placement::Normal, &replaceable);
+    assert_eq!(world.get(Pos::new(2, 1, 0)), block![planks]);
+
+    // Keep cells never change the world.
+    world.place_structure_block(Pos::new(2, 1, 0), block![sand], Placement::Keep, &replaceable);
+    assert_eq!(world.get(Pos::new(2, 1, 0)), block![planks]);
+
+    // Force cells replace anything, including with air.
+    world.place_structure_block(Pos::new(2, 1, 0), block![air], Placement::Force, &replaceable);
+    assert_eq!(world.get(Pos::new(2, 1, 0)), block![air]);
+
+    // If replaceable cells only replace blocks in the filter.
+    world.place_structure_block(
+      Pos::new(1, 1, 0),
+      block![sand],
+      Placement::IfReplaceable,
+      &replaceable,
+    );
+    assert_eq!(world.get(Pos::new(1, 1, 0)), block![sand]);
+    world.place_structure_block(
+      Pos::new(0, 0, 0),
+      block![sand],
+      Placement::IfReplaceable,
+      &replaceable,
+    );
+    assert_eq!(world.get(Pos::new(0, 0, 0)), block![stone]);
+
+    // Foundations fill down until they hit a block that can't be replaced.
+    world.set(Pos::new(3, 2, 0), block![tallgrass]);
+    world.place_structure_block(
+      Pos::new(3, 4, 0),
+      block![cobblestone],
+      Placement::Foundation,
+      &replaceable,
+    );
+    for y in 1..=4 {
+      assert_eq!(world.get(Pos::new(3, y, 0)), block![cobblestone]);
+    }
+    assert_eq!(world.get(Pos::new(3, 0, 0)), block![stone]);
+    assert_eq!(world.get(Pos::new(3, 5, 0)), block![air]);
+  }
+}
@@ -4,8 +4,8 @@ use std::{collections::HashMap, sync::Arc};
 use crossbeam_channel::{Receiver, Sender};
 use parking_lot::{Mutex, RwLock};
 use rgen_base::{
-  Biome, BiomeId, BlockData, BlockId, BlockKind, Chunk, ChunkPos, Pos, PropMapOwned, PropType,
-  PropValueOwned, StateId, block_kind,
+  Biome, BiomeId, BlockData, BlockEntity, BlockId, BlockKind, Chunk, ChunkPos, Entity, Pos,
+  PropMapOwned, PropType, PropValueOwned, StateId, block_kind,
 };
 
 mod block;
@@ -97,12 +97,25 @@ pub struct PartialWorld<'a> {
 #[derive(Default)]
 struct UndoFrame {
   blocks: Vec<(Pos, StateId)>,
+
+  // Block entities and entities are only stored once the placement succeeds.
+  block_entities: Vec<(Pos, BlockEntity)>,
+  entities:       Vec<(Pos, Entity)>,
 }
 
 pub trait PartialWorldStorage {
   fn get(&self, pos: Pos) -> StateId;
   fn set(&mut self, pos: Pos, block: StateId);
   fn surfaces(&self, pos: Pos) -> &[u8];
+
+  /// Stores a block entity. Storages that can't hold block entities ignore
+  /// them.
+  fn set_block_entity(&mut self, pos: Pos, block_entity: BlockEntity) {
+    let _ = (pos, block_entity);
+  }
+
+  /// Stores an entity to spawn. Storages that can't hold entities ignore them.
+  fn add_entity(&mut self, pos: Pos, entity: Entity) { let _ = (pos, entity); }
 }
 
 impl<'a> PartialWorld<'a> {